@@ -0,0 +1,243 @@
+use std::fs;
+use std::path::Path;
+
+use crate::Error;
+
+/// Largest attachment the API accepts, in bytes.
+pub const MAX_ATTACHMENT_SIZE: usize = 5 * 1024 * 1024;
+
+/// An image attached to a [`Message`](crate::Message).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Attachment {
+    filename: String,
+    mime_type: String,
+    data: Vec<u8>,
+}
+
+impl Attachment {
+    /// Reads an image from disk, inferring its MIME type from the file
+    /// extension or, failing that, from the file contents.
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+
+        // Check the size before reading so oversized files are never loaded
+        let size = fs::metadata(path)
+            .map_err(|e| Error::io(format_args!("Cannot read attachment {}", path.display()), e))?
+            .len();
+        if size > MAX_ATTACHMENT_SIZE as u64 {
+            return Err(Error::Validation(format!(
+                "Attachment {} is {} bytes, the maximum is {}",
+                path.display(),
+                size,
+                MAX_ATTACHMENT_SIZE
+            )));
+        }
+
+        let data = fs::read(path)
+            .map_err(|e| Error::io(format_args!("Cannot read attachment {}", path.display()), e))?;
+        let mime_type = mime_type_for_path(path)
+            .or_else(|| sniff_mime_type(&data))
+            .ok_or_else(|| {
+                Error::Validation(format!(
+                    "Cannot determine the image type of {}; supported types are JPEG, PNG, GIF, WebP and BMP",
+                    path.display()
+                ))
+            })?;
+        let filename = path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_else(|| "attachment".to_string());
+
+        Ok(Attachment {
+            filename,
+            mime_type: mime_type.to_string(),
+            data,
+        })
+    }
+
+    /// Creates an attachment from in-memory image data.
+    pub fn from_bytes(
+        filename: impl Into<String>,
+        mime_type: impl Into<String>,
+        data: impl Into<Vec<u8>>,
+    ) -> Self {
+        Attachment {
+            filename: filename.into(),
+            mime_type: mime_type.into(),
+            data: data.into(),
+        }
+    }
+
+    pub fn filename(&self) -> &str {
+        &self.filename
+    }
+
+    pub fn mime_type(&self) -> &str {
+        &self.mime_type
+    }
+
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    pub(crate) fn validate(&self) -> Result<(), Error> {
+        if self.data.len() > MAX_ATTACHMENT_SIZE {
+            return Err(Error::Validation(format!(
+                "Attachment {} is {} bytes, the maximum is {}",
+                self.filename,
+                self.data.len(),
+                MAX_ATTACHMENT_SIZE
+            )));
+        }
+        if !self.mime_type.starts_with("image/") {
+            return Err(Error::Validation(format!(
+                "Attachment {} has type {}, only images are supported",
+                self.filename, self.mime_type
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// How an attachment is put on the wire.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum AttachmentEncoding {
+    /// As a file part of a `multipart/form-data` body.
+    Multipart,
+    /// As the `attachment_base64` and `attachment_type` form fields.
+    Base64,
+}
+
+/// Infers an image MIME type from a file extension.
+pub fn mime_type_for_path(path: &Path) -> Option<&'static str> {
+    let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+    match extension.as_str() {
+        "jpg" | "jpeg" => Some("image/jpeg"),
+        "png" => Some("image/png"),
+        "gif" => Some("image/gif"),
+        "webp" => Some("image/webp"),
+        "bmp" => Some("image/bmp"),
+        _ => None,
+    }
+}
+
+fn sniff_mime_type(data: &[u8]) -> Option<&'static str> {
+    if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        Some("image/png")
+    } else if data.starts_with(b"GIF87a") || data.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if data.len() >= 12 && &data[..4] == b"RIFF" && &data[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if data.starts_with(b"BM") {
+        Some("image/bmp")
+    } else {
+        None
+    }
+}
+
+/// Encodes data as standard, padded base64.
+pub(crate) fn base64_encode(data: &[u8]) -> String {
+    const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+    let mut encoded = String::with_capacity(data.len().div_ceil(3) * 4);
+    for chunk in data.chunks(3) {
+        let b = [
+            chunk[0],
+            chunk.get(1).copied().unwrap_or(0),
+            chunk.get(2).copied().unwrap_or(0),
+        ];
+        let n = (u32::from(b[0]) << 16) | (u32::from(b[1]) << 8) | u32::from(b[2]);
+        encoded.push(ALPHABET[(n >> 18) as usize & 63] as char);
+        encoded.push(ALPHABET[(n >> 12) as usize & 63] as char);
+        encoded.push(if chunk.len() > 1 {
+            ALPHABET[(n >> 6) as usize & 63] as char
+        } else {
+            '='
+        });
+        encoded.push(if chunk.len() > 2 {
+            ALPHABET[n as usize & 63] as char
+        } else {
+            '='
+        });
+    }
+    encoded
+}
+
+/// Builds a `multipart/form-data` body from text fields and a file part.
+pub(crate) fn multipart_body(
+    boundary: &str,
+    fields: &[(&str, String)],
+    attachment: &Attachment,
+) -> Vec<u8> {
+    let mut body = Vec::new();
+    for (name, value) in fields {
+        body.extend_from_slice(
+            format!(
+                "--{}\r\nContent-Disposition: form-data; name=\"{}\"\r\n\r\n{}\r\n",
+                boundary, name, value
+            )
+            .as_bytes(),
+        );
+    }
+    body.extend_from_slice(
+        format!(
+            "--{}\r\nContent-Disposition: form-data; name=\"attachment\"; filename=\"{}\"\r\nContent-Type: {}\r\n\r\n",
+            boundary,
+            attachment.filename.replace(['"', '\r', '\n'], "_"),
+            attachment.mime_type
+        )
+        .as_bytes(),
+    );
+    body.extend_from_slice(&attachment.data);
+    body.extend_from_slice(format!("\r\n--{}--\r\n", boundary).as_bytes());
+    body
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base64_encode() {
+        assert_eq!(base64_encode(b""), "");
+        assert_eq!(base64_encode(b"f"), "Zg==");
+        assert_eq!(base64_encode(b"fo"), "Zm8=");
+        assert_eq!(base64_encode(b"foo"), "Zm9v");
+        assert_eq!(base64_encode(b"foobar"), "Zm9vYmFy");
+        assert_eq!(base64_encode(&[0xFF, 0xD8, 0xFF, 0xE0]), "/9j/4A==");
+    }
+
+    #[test]
+    fn test_mime_type_inference() {
+        assert_eq!(
+            mime_type_for_path(Path::new("graph.PNG")),
+            Some("image/png")
+        );
+        assert_eq!(
+            mime_type_for_path(Path::new("a/b.jpeg")),
+            Some("image/jpeg")
+        );
+        assert_eq!(mime_type_for_path(Path::new("notes.txt")), None);
+        assert_eq!(mime_type_for_path(Path::new("noextension")), None);
+
+        assert_eq!(sniff_mime_type(b"\x89PNG\r\n\x1a\nrest"), Some("image/png"));
+        assert_eq!(sniff_mime_type(b"GIF89a..."), Some("image/gif"));
+        assert_eq!(sniff_mime_type(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_mime_type(b"plain text"), None);
+    }
+
+    #[test]
+    fn test_multipart_body() {
+        let attachment = Attachment::from_bytes("graph.png", "image/png", b"PNGDATA".to_vec());
+        let fields = vec![("token", "abc".to_string()), ("message", "hi".to_string())];
+        let body = multipart_body("XyZ", &fields, &attachment);
+
+        let expected = "--XyZ\r\nContent-Disposition: form-data; name=\"token\"\r\n\r\nabc\r\n\
+                        --XyZ\r\nContent-Disposition: form-data; name=\"message\"\r\n\r\nhi\r\n\
+                        --XyZ\r\nContent-Disposition: form-data; name=\"attachment\"; filename=\"graph.png\"\r\n\
+                        Content-Type: image/png\r\n\r\nPNGDATA\r\n--XyZ--\r\n";
+        assert_eq!(String::from_utf8(body).unwrap(), expected);
+    }
+}
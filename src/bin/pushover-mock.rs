@@ -0,0 +1,103 @@
+use std::env;
+use std::fs;
+use std::io::{self, Write};
+use std::net::Ipv4Addr;
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use pushover::mock::MockServer;
+
+fn usage() -> ! {
+    eprintln!("Usage: pushover-mock [--port <port>] [--ca-file <path>]");
+    eprintln!();
+    eprintln!("Runs a local HTTPS stand-in for the Pushover API and logs every request.");
+    eprintln!();
+    eprintln!("Options:");
+    eprintln!("  --port <port>     Port to listen on (default: any free port)");
+    eprintln!(
+        "  --ca-file <path>  Where to write the CA certificate (default: in the temp directory)"
+    );
+    eprintln!("  -h, --help        Show this help message");
+    process::exit(1);
+}
+
+fn main() {
+    let args: Vec<String> = env::args().collect();
+    let mut port = 0;
+    let mut ca_file = env::temp_dir().join("pushover-mock-ca.pem");
+
+    let mut i = 1;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--port" => {
+                i += 1;
+                port = match args.get(i).map(|p| p.parse::<u16>()) {
+                    Some(Ok(port)) => port,
+                    _ => {
+                        eprintln!("Option --port requires a port number.");
+                        process::exit(1);
+                    }
+                };
+            }
+            "--ca-file" => {
+                i += 1;
+                match args.get(i) {
+                    Some(path) => ca_file = PathBuf::from(path),
+                    None => {
+                        eprintln!("Option --ca-file requires an argument.");
+                        process::exit(1);
+                    }
+                }
+            }
+            "-h" | "--help" => usage(),
+            other => {
+                eprintln!("Unknown option: {}", other);
+                usage();
+            }
+        }
+        i += 1;
+    }
+
+    let server = match MockServer::bind((Ipv4Addr::LOCALHOST, port)) {
+        Ok(server) => server,
+        Err(e) => {
+            eprintln!("Error starting mock server: {}", e);
+            process::exit(1);
+        }
+    };
+    if let Err(e) = fs::write(&ca_file, server.ca_pem()) {
+        eprintln!("Error writing {}: {}", ca_file.display(), e);
+        process::exit(1);
+    }
+
+    println!("export PUSHOVER_API_URL={}", server.url());
+    println!("export PUSHOVER_CA_FILE={}", ca_file.display());
+
+    let mut logged = 0;
+    loop {
+        let requests = server.requests();
+        let mut stdout = io::stdout().lock();
+        for request in &requests[logged..] {
+            let _ = writeln!(stdout, "{} {}", request.method, request.path);
+            for (name, value) in &request.fields {
+                let _ = writeln!(stdout, "  {}={}", name, value);
+            }
+            for file in &request.files {
+                let _ = writeln!(
+                    stdout,
+                    "  {}: {} ({}, {} bytes)",
+                    file.name,
+                    file.filename,
+                    file.content_type,
+                    file.data.len()
+                );
+            }
+        }
+        let _ = stdout.flush();
+        logged = requests.len();
+        drop(stdout);
+        thread::sleep(Duration::from_millis(200));
+    }
+}
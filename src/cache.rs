@@ -0,0 +1,147 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::Error;
+
+/// Per-user cache directory: `$XDG_CACHE_HOME/pushover`, else `~/.cache/pushover`.
+pub(crate) fn cache_dir() -> Option<PathBuf> {
+    if let Some(cache_home) = env::var_os("XDG_CACHE_HOME").filter(|dir| !dir.is_empty()) {
+        return Some(PathBuf::from(cache_home).join("pushover"));
+    }
+    env::var_os("HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(|home| PathBuf::from(home).join(".cache/pushover"))
+}
+
+#[derive(Serialize, Deserialize)]
+struct Entry {
+    checked_at: u64,
+    value: serde_json::Value,
+}
+
+/// Returns the value stored under `key` in `cache_file` if it is younger than
+/// `ttl`, and otherwise calls `fetch` and stores what it returns.
+///
+/// Errors are never cached. Keys are stored as hashes (see [`hash_key`]), so
+/// credentials can be part of them. A cache file that cannot be read or
+/// written is ignored.
+pub(crate) fn cached<T, F>(
+    cache_file: &Path,
+    key: &[&str],
+    ttl: Duration,
+    fetch: F,
+) -> Result<T, Error>
+where
+    T: Serialize + DeserializeOwned,
+    F: FnOnce() -> Result<T, Error>,
+{
+    let key = hash_key(key);
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let fresh = |entry: &Entry| now.saturating_sub(entry.checked_at) < ttl.as_secs();
+
+    let mut entries: BTreeMap<String, Entry> = fs::read(cache_file)
+        .ok()
+        .and_then(|content| serde_json::from_slice(&content).ok())
+        .unwrap_or_default();
+    if let Some(entry) = entries.get(&key).filter(|entry| fresh(entry)) {
+        if let Ok(value) = serde_json::from_value(entry.value.clone()) {
+            return Ok(value);
+        }
+    }
+
+    let value = fetch()?;
+    entries.retain(|_, entry| fresh(entry));
+    if let Ok(json) = serde_json::to_value(&value) {
+        entries.insert(
+            key,
+            Entry {
+                checked_at: now,
+                value: json,
+            },
+        );
+    }
+    if let Ok(content) = serde_json::to_vec(&entries) {
+        if let Some(parent) = cache_file.parent() {
+            let _ = fs::create_dir_all(parent);
+        }
+        let _ = fs::write(cache_file, content);
+    }
+    Ok(value)
+}
+
+/// Hashes the parts of a key with 64-bit FNV-1a, which unlike the standard
+/// library's hasher gives the same result with every Rust release, so a
+/// toolchain upgrade does not orphan the entries on disk. Each part is
+/// preceded by its length, so `["ab", "c"]` and `["a", "bc"]` differ.
+fn hash_key(parts: &[&str]) -> String {
+    const OFFSET_BASIS: u64 = 0xcbf2_9ce4_8422_2325;
+    const PRIME: u64 = 0x0100_0000_01b3;
+
+    let mut hash = OFFSET_BASIS;
+    for part in parts {
+        let length = (part.len() as u64).to_le_bytes();
+        for byte in length.iter().chain(part.as_bytes()) {
+            hash ^= u64::from(*byte);
+            hash = hash.wrapping_mul(PRIME);
+        }
+    }
+    format!("{:016x}", hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cached() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let cache_file = dir.path().join("cache.json");
+        let ttl = Duration::from_secs(60);
+
+        let value: Vec<String> = cached(&cache_file, &["key"], ttl, || {
+            Ok(vec!["fetched".to_string()])
+        })
+        .unwrap();
+        assert_eq!(value, vec!["fetched"]);
+        let value: Vec<String> =
+            cached(&cache_file, &["key"], ttl, || panic!("fetched twice")).unwrap();
+        assert_eq!(value, vec!["fetched"]);
+        assert!(!fs::read_to_string(&cache_file).unwrap().contains("key"));
+
+        let err = cached::<Vec<String>, _>(&cache_file, &["other"], ttl, || {
+            Err(Error::Validation("failed".to_string()))
+        });
+        assert!(err.is_err());
+        let value: Vec<String> = cached(&cache_file, &["other"], ttl, || Ok(Vec::new())).unwrap();
+        assert!(value.is_empty());
+
+        // Expired entries are fetched again
+        let value: Vec<String> = cached(&cache_file, &["key"], Duration::ZERO, || {
+            Ok(vec!["refreshed".to_string()])
+        })
+        .unwrap();
+        assert_eq!(value, vec!["refreshed"]);
+    }
+
+    #[test]
+    fn test_hash_key_is_stable() {
+        assert_eq!(
+            hash_key(&[
+                "https://api.pushover.net/1",
+                "azGDORePK8gMaC0QOYAMyEEuzJnyUi"
+            ]),
+            "b24658612fc0281a"
+        );
+        assert_eq!(hash_key(&["ab", "c"]), "7e60470bf599cad6");
+        assert_eq!(hash_key(&["a", "bc"]), "ba1e1f0e0704d8ea");
+    }
+}
@@ -0,0 +1,543 @@
+use std::fs;
+use std::io::{self, BufReader, Read, Write};
+use std::path::Path;
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rustls::{
+    Certificate, ClientConfig, ClientConnection, OwnedTrustAnchor, RootCertStore, StreamOwned,
+};
+use serde::de::DeserializeOwned;
+use webpki_roots::TLS_SERVER_ROOTS;
+
+use crate::attachment::{multipart_body, AttachmentEncoding};
+use crate::http::HttpResponse;
+use crate::message::split_devices;
+use crate::proxy::{bypasses_proxy, no_proxy_from_environment, proxy_from_environment, Proxy};
+use crate::response::parse_api_response;
+use crate::retry::RetryPolicy;
+use crate::timeout::{
+    Deadline, Timeouts, DEFAULT_CONNECT_TIMEOUT, DEFAULT_READ_TIMEOUT, DEFAULT_TIMEOUT,
+};
+use crate::{
+    parse_url, url_encode, AppLimits, Config, Error, Message, NotificationConfig, SendResponse,
+    EMERGENCY_PRIORITY,
+};
+
+/// Base URL of the Pushover API; endpoint paths are appended to it.
+pub const PUSHOVER_API_URL: &str = "https://api.pushover.net/1";
+
+/// A Pushover API client holding credentials and a reusable TLS configuration.
+pub struct Client {
+    user: String,
+    token: String,
+    defaults: NotificationConfig,
+    api_url: String,
+    root_store: RootCertStore,
+    tls_config: Arc<ClientConfig>,
+    proxy: Option<Proxy>,
+    no_proxy: Option<String>,
+    timeouts: Timeouts,
+    retry_policy: RetryPolicy,
+}
+
+impl Client {
+    /// Creates a client for the given user key and application token.
+    pub fn new(user: impl Into<String>, token: impl Into<String>) -> Self {
+        let mut root_store = RootCertStore::empty();
+        root_store.add_trust_anchors(TLS_SERVER_ROOTS.iter().map(|ta| {
+            OwnedTrustAnchor::from_subject_spki_name_constraints(
+                ta.subject,
+                ta.spki,
+                ta.name_constraints,
+            )
+        }));
+        let tls_config = build_tls_config(&root_store);
+
+        Client {
+            user: user.into(),
+            token: token.into(),
+            defaults: NotificationConfig::default(),
+            api_url: PUSHOVER_API_URL.to_string(),
+            root_store,
+            tls_config,
+            proxy: None,
+            no_proxy: None,
+            timeouts: Timeouts::default(),
+            retry_policy: RetryPolicy::default(),
+        }
+    }
+
+    /// Creates a client from a loaded configuration, including its
+    /// `[notification]` defaults, API URL and extra CA certificate.
+    ///
+    /// Requests go through the `[network]` proxy, else the one in
+    /// `HTTPS_PROXY` or `https_proxy`, unless `NO_PROXY` or `no_proxy`
+    /// excludes the API host.
+    pub fn from_config(config: &Config) -> Result<Self, Error> {
+        let mut client = Client::new(&config.pushover.user, &config.pushover.token);
+        if let Some(notification) = &config.notification {
+            client.defaults = notification.clone();
+        }
+        if let Some(api_url) = &config.pushover.api_url {
+            client = client.with_api_url(api_url)?;
+        }
+        if let Some(ca_file) = &config.pushover.ca_file {
+            client = client.with_ca_file(ca_file)?;
+        }
+        let proxy = config
+            .network
+            .as_ref()
+            .and_then(|network| network.proxy.clone())
+            .or_else(proxy_from_environment);
+        if let Some(proxy) = proxy.filter(|proxy| !proxy.trim().is_empty()) {
+            client = client.with_proxy(&proxy)?;
+        }
+        if let Some(no_proxy) = no_proxy_from_environment() {
+            client = client.with_no_proxy(no_proxy);
+        }
+        if let Some(network) = &config.network {
+            let seconds =
+                |setting: Option<u64>, default| setting.map_or(default, Duration::from_secs);
+            client = client.with_timeouts(Timeouts {
+                connect: seconds(network.connect_timeout_secs, DEFAULT_CONNECT_TIMEOUT),
+                read: seconds(network.read_timeout_secs, DEFAULT_READ_TIMEOUT),
+                overall: seconds(network.timeout_secs, DEFAULT_TIMEOUT),
+            });
+            if let Some(retries) = network.retries {
+                client = client.with_retry_policy(RetryPolicy {
+                    max_retries: retries,
+                    ..RetryPolicy::default()
+                });
+            }
+        }
+        Ok(client)
+    }
+
+    /// Points the client at a different API base URL, such as a local mock
+    /// server. Endpoint paths like `messages.json` are appended to it.
+    pub fn with_api_url(mut self, api_url: &str) -> Result<Self, Error> {
+        parse_url(api_url)?;
+        self.api_url = api_url.trim_end_matches('/').to_string();
+        Ok(self)
+    }
+
+    /// Trusts the CA certificates in a PEM file in addition to the bundled roots.
+    pub fn with_ca_file(self, path: impl AsRef<Path>) -> Result<Self, Error> {
+        let path = path.as_ref();
+        let pem = fs::read(path)
+            .map_err(|e| Error::io(format_args!("Cannot read CA file {}", path.display()), e))?;
+        self.with_ca_pem(&pem).map_err(|e| match e {
+            Error::Tls(message) => {
+                Error::Tls(format!("Invalid CA file {}: {}", path.display(), message))
+            }
+            other => other,
+        })
+    }
+
+    /// Trusts the PEM-encoded CA certificates in `pem` in addition to the bundled roots.
+    pub fn with_ca_pem(mut self, pem: &[u8]) -> Result<Self, Error> {
+        let certs = rustls_pemfile::certs(&mut BufReader::new(pem))
+            .map_err(|e| Error::Tls(format!("malformed PEM data: {}", e)))?;
+        if certs.is_empty() {
+            return Err(Error::Tls("no PEM certificates found".to_string()));
+        }
+        for cert in certs {
+            self.root_store
+                .add(&Certificate(cert))
+                .map_err(|e| Error::Tls(format!("unusable certificate: {}", e)))?;
+        }
+        self.tls_config = build_tls_config(&self.root_store);
+        Ok(self)
+    }
+
+    /// Tunnels requests through an HTTP proxy with CONNECT, given as
+    /// `http://[user:password@]host[:port]`. User and password are sent as
+    /// Basic proxy authentication.
+    pub fn with_proxy(mut self, url: &str) -> Result<Self, Error> {
+        self.proxy = Some(Proxy::parse(url)?);
+        Ok(self)
+    }
+
+    /// Connects directly to the hosts in a comma-separated `NO_PROXY` style
+    /// list, such as `localhost,.internal`, instead of using the proxy.
+    pub fn with_no_proxy(mut self, hosts: impl Into<String>) -> Self {
+        self.no_proxy = Some(hosts.into());
+        self
+    }
+
+    /// Replaces the default connect, read and overall [`Timeouts`].
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Self {
+        self.timeouts = timeouts;
+        self
+    }
+
+    /// Replaces the default [`RetryPolicy`]; `RetryPolicy::never()` makes
+    /// every request a single attempt.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn retry_policy(&self) -> RetryPolicy {
+        self.retry_policy
+    }
+
+    pub fn timeouts(&self) -> Timeouts {
+        self.timeouts
+    }
+
+    pub fn api_url(&self) -> &str {
+        &self.api_url
+    }
+
+    /// Replaces the application token, e.g. for a per-invocation override.
+    pub fn with_token(mut self, token: impl Into<String>) -> Self {
+        self.token = token.into();
+        self
+    }
+
+    pub fn user(&self) -> &str {
+        &self.user
+    }
+
+    pub(crate) fn token(&self) -> &str {
+        &self.token
+    }
+
+    pub fn root_store(&self) -> &RootCertStore {
+        &self.root_store
+    }
+
+    /// Validates and sends a notification.
+    ///
+    /// Returns the request id, and the receipt for emergency-priority
+    /// messages. When Pushover rejects the message the error is an
+    /// [`ApiError`] carrying Pushover's own error messages.
+    pub fn send(&self, message: &Message) -> Result<SendResponse, Error> {
+        let message = self.with_defaults(message);
+        message.validate()?;
+        let response = match &message.attachment {
+            Some((attachment, AttachmentEncoding::Multipart)) => {
+                let boundary = multipart_boundary();
+                let body = multipart_body(&boundary, &self.form_fields(&message), attachment);
+                let content_type = format!("multipart/form-data; boundary={}", boundary);
+                let url = format!("{}/messages.json", self.api_url);
+                self.request("POST", &url, Some((&content_type, &body)))?
+            }
+            _ => self.post_form("messages.json", self.form_fields(&message))?,
+        };
+        let mut sent: SendResponse = parse_api_response(response.status, &response.body)?;
+        sent.limits = AppLimits::from_headers(&response);
+        Ok(sent)
+    }
+
+    /// Fills settings the message leaves unset from the configured defaults.
+    fn with_defaults(&self, message: &Message) -> Message {
+        let mut message = message.clone();
+        if message.sound.is_none() {
+            message.sound = self.defaults.sound.clone();
+        }
+        if let (true, Some(devices)) = (message.devices.is_empty(), &self.defaults.device) {
+            message.devices.extend(split_devices(devices));
+        }
+        if message.priority == EMERGENCY_PRIORITY {
+            message.retry = message.retry.or(self.defaults.retry);
+            message.expire = message.expire.or(self.defaults.expire);
+            if message.callback.is_none() {
+                message.callback = self.defaults.callback.clone();
+            }
+        }
+        message
+    }
+
+    fn form_fields(&self, message: &Message) -> Vec<(&'static str, String)> {
+        let mut fields = vec![("token", self.token.clone()), ("user", self.user.clone())];
+        fields.extend(message.form_fields());
+        fields
+    }
+
+    /// POSTs form fields to an API endpoint and parses the JSON response.
+    /// The application token is added to the fields if not already present.
+    pub(crate) fn post<T: DeserializeOwned>(
+        &self,
+        endpoint: &str,
+        fields: Vec<(&'static str, String)>,
+    ) -> Result<T, Error> {
+        let response = self.post_form(endpoint, fields)?;
+        parse_api_response(response.status, &response.body)
+    }
+
+    fn post_form(
+        &self,
+        endpoint: &str,
+        mut fields: Vec<(&'static str, String)>,
+    ) -> Result<HttpResponse, Error> {
+        if !fields.iter().any(|(name, _)| *name == "token") {
+            fields.insert(0, ("token", self.token.clone()));
+        }
+        let url = format!("{}/{}", self.api_url, endpoint);
+        let form_data = encode_form(&fields);
+        self.request(
+            "POST",
+            &url,
+            Some(("application/x-www-form-urlencoded", form_data.as_bytes())),
+        )
+    }
+
+    /// GETs an API endpoint, authenticated with the application token.
+    pub(crate) fn get<T: DeserializeOwned>(&self, endpoint: &str) -> Result<T, Error> {
+        let url = format!(
+            "{}/{}?token={}",
+            self.api_url,
+            endpoint,
+            url_encode(&self.token)
+        );
+        let response = self.request("GET", &url, None)?;
+        parse_api_response(response.status, &response.body)
+    }
+
+    /// Makes a request, repeating it as the [`RetryPolicy`] allows within
+    /// the overall timeout, and returns the last response.
+    fn request(
+        &self,
+        method: &str,
+        url: &str,
+        body: Option<(&str, &[u8])>,
+    ) -> Result<HttpResponse, Error> {
+        // One deadline for every attempt and the waits between them
+        let deadline = Deadline::start(self.timeouts);
+        let mut retry = 0;
+        loop {
+            let result = self.attempt(method, url, body, &deadline);
+            let outcome = match &result {
+                Ok(response) => Ok((response.status, response.header("Retry-After"))),
+                Err(e) => Err(e),
+            };
+            let Some(delay) = self.retry_policy.delay(retry, outcome) else {
+                return result;
+            };
+            match deadline.remaining("before the request could be retried") {
+                Ok(remaining) => thread::sleep(delay.min(remaining)),
+                // An attempt that timed out already says what it waited for
+                Err(timeout) => {
+                    return match result {
+                        Err(Error::Timeout(message)) => Err(Error::Timeout(message)),
+                        _ => Err(timeout),
+                    }
+                }
+            }
+            retry += 1;
+        }
+    }
+
+    fn attempt(
+        &self,
+        method: &str,
+        url: &str,
+        body: Option<(&str, &[u8])>,
+        deadline: &Deadline,
+    ) -> Result<HttpResponse, Error> {
+        let (host, port, path) = parse_url(url)?;
+
+        // Connect to server
+        let server_name = rustls::ServerName::try_from(host.as_str())
+            .map_err(|_| Error::Config(format!("Invalid host name in URL {}", url)))?;
+        let conn = ClientConnection::new(Arc::clone(&self.tls_config), server_name)?;
+        let proxy = self.proxy.as_ref().filter(|_| {
+            !self
+                .no_proxy
+                .as_deref()
+                .is_some_and(|no_proxy| bypasses_proxy(no_proxy, &host))
+        });
+        let sock = match proxy {
+            Some(proxy) => proxy.connect(&host, port, deadline)?,
+            None => deadline.connect(&host, port)?,
+        };
+        let mut tls = StreamOwned::new(conn, sock);
+
+        // Build HTTP request
+        let mut request = format!(
+            "{} {} HTTP/1.1\r\n\
+             Host: {}\r\n\
+             Connection: close\r\n\
+             User-Agent: pushover-rust/1.0\r\n",
+            method, path, host
+        );
+        if let Some((content_type, body)) = body {
+            request.push_str(&format!(
+                "Content-Type: {}\r\n\
+                 Content-Length: {}\r\n",
+                content_type,
+                body.len()
+            ));
+        }
+        request.push_str("\r\n");
+
+        // Send request
+        let sending = format!("sending the request to {}", host);
+        deadline.arm(&tls.sock, &sending)?;
+        tls.write_all(request.as_bytes())
+            .map_err(|e| deadline.io_error(e, &sending))?;
+        if let Some((_, body)) = body {
+            tls.write_all(body)
+                .map_err(|e| deadline.io_error(e, &sending))?;
+        }
+
+        // Read response, re-arming the timeouts before each read so the
+        // overall deadline holds however slowly the server trickles data
+        let waiting = format!("waiting for the response from {}", host);
+        let mut response = Vec::new();
+        let mut buffer = [0u8; 8192];
+        loop {
+            deadline.arm(&tls.sock, &waiting)?;
+            match tls.read(&mut buffer) {
+                Ok(0) => break,
+                Ok(n) => response.extend_from_slice(&buffer[..n]),
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+                // A server closing without a TLS close_notify has still answered;
+                // the parser tells whether the response is complete
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof && !response.is_empty() => break,
+                Err(e) => return Err(deadline.io_error(e, &waiting)),
+            }
+        }
+
+        HttpResponse::parse(&response)
+    }
+}
+
+/// Returns a multipart boundary that is unique enough not to occur in the body.
+fn multipart_boundary() -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or_default();
+    format!("pushover-rust-{:x}{:x}", nanos, std::process::id())
+}
+
+fn encode_form(fields: &[(&str, String)]) -> String {
+    fields
+        .iter()
+        .map(|(name, value)| format!("{}={}", name, url_encode(value)))
+        .collect::<Vec<_>>()
+        .join("&")
+}
+
+fn build_tls_config(root_store: &RootCertStore) -> Arc<ClientConfig> {
+    Arc::new(
+        ClientConfig::builder()
+            .with_safe_defaults()
+            .with_root_certificates(root_store.clone())
+            .with_no_client_auth(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_form_data_uses_notification_defaults() {
+        let config: Config = toml::from_str(
+            r#"
+[pushover]
+user = "user_key"
+token = "app_token"
+
+[notification]
+sound = "cosmic"
+device = "iphone"
+"#,
+        )
+        .unwrap();
+        let client = Client::from_config(&config).unwrap();
+
+        let message = Message::new("Disk full").title("Alert").priority(1);
+        let form = encode_form(&client.form_fields(&client.with_defaults(&message)));
+        assert_eq!(
+            form,
+            "token=app_token&user=user_key&title=Alert&message=Disk+full&priority=1&sound=cosmic&device=iphone"
+        );
+
+        let message = Message::new("hi").sound("siren").device("pixel");
+        let form = encode_form(&client.form_fields(&client.with_defaults(&message)));
+        assert!(form.contains("sound=siren"));
+        assert!(form.contains("device=pixel"));
+        assert!(!form.contains("title="));
+        assert!(!form.contains("cosmic"));
+        assert!(!form.contains("iphone"));
+    }
+
+    #[test]
+    fn test_emergency_defaults_from_config() {
+        let config: Config = toml::from_str(
+            r#"
+[pushover]
+user = "user_key"
+token = "app_token"
+
+[notification]
+retry = 60
+expire = 3600
+callback = "https://example.com/ack"
+"#,
+        )
+        .unwrap();
+        let client = Client::from_config(&config).unwrap();
+
+        let message = client.with_defaults(&Message::new("down").priority(EMERGENCY_PRIORITY));
+        assert!(message.validate().is_ok());
+        assert_eq!(message.retry, Some(60));
+        assert_eq!(message.expire, Some(3600));
+        assert_eq!(message.callback.as_deref(), Some("https://example.com/ack"));
+
+        let message = Message::new("down").priority(EMERGENCY_PRIORITY).retry(120);
+        assert_eq!(client.with_defaults(&message).retry, Some(120));
+
+        // Emergency defaults are not applied to other priorities
+        let message = client.with_defaults(&Message::new("fyi").priority(1));
+        assert!(message.retry.is_none());
+        assert!(message.expire.is_none());
+    }
+
+    #[test]
+    fn test_api_url_override() {
+        let client = Client::new("user_key", "app_token");
+        assert_eq!(client.api_url(), PUSHOVER_API_URL);
+
+        let client = client.with_api_url("https://localhost:8443/1/").unwrap();
+        assert_eq!(client.api_url(), "https://localhost:8443/1");
+
+        let client = Client::new("user_key", "app_token");
+        assert!(client.with_api_url("http://localhost:8080/1").is_err());
+
+        let config: Config = toml::from_str(
+            r#"
+[pushover]
+user = "user_key"
+token = "app_token"
+api_url = "https://mock.example:9443/1"
+"#,
+        )
+        .unwrap();
+        let client = Client::from_config(&config).unwrap();
+        assert_eq!(client.api_url(), "https://mock.example:9443/1");
+    }
+
+    #[test]
+    fn test_ca_pem_rejects_invalid_input() {
+        let client = Client::new("user_key", "app_token");
+        assert!(client.with_ca_pem(b"not a certificate").is_err());
+
+        let client = Client::new("user_key", "app_token");
+        assert!(client.with_ca_file("/nonexistent/ca.pem").is_err());
+    }
+
+    #[test]
+    fn test_with_token_overrides_config_token() {
+        let client = Client::new("user_key", "app_token").with_token("override");
+        let form = encode_form(&client.form_fields(&Message::new("hi")));
+        assert!(form.starts_with("token=override&user=user_key&"));
+    }
+}
@@ -0,0 +1,1182 @@
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Deserializer, Serialize};
+
+use crate::secret::SecretSources;
+use crate::Error;
+
+/// System-wide configuration file.
+pub const SYSTEM_CONFIG_PATH: &str = "/etc/pushover/config.toml";
+
+/// Development fallback, relative to the working directory, used in place of
+/// the system-wide file when that does not exist.
+pub const LOCAL_CONFIG_PATH: &str = "etc/pushover/config.toml";
+
+/// The `[pushover]` section.
+///
+/// The user key and token are given either in plain text (`user`, `token`) or
+/// through one of `*_file`, `*_env` or `*_command`, which [`load_config`]
+/// resolves into `user` and `token`.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+#[serde(try_from = "RawPushoverConfig")]
+pub struct PushoverConfig {
+    pub user: String,
+    pub token: String,
+    #[serde(default)]
+    pub default_title: Option<String>,
+    /// API base URL, e.g. to point at a local mock server
+    #[serde(default)]
+    pub api_url: Option<String>,
+    /// Extra PEM CA certificate file to trust besides the bundled roots
+    #[serde(default)]
+    pub ca_file: Option<PathBuf>,
+    /// Refuse to load config files with unsafe permissions instead of
+    /// warning about them
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub strict_permissions: bool,
+    /// Check the user key and device with Pushover before sending, caching
+    /// a successful result for a day
+    #[serde(default, skip_serializing_if = "is_false")]
+    pub validate_on_load: bool,
+    /// Warn after sending when fewer messages than this are left in the
+    /// application's monthly quota; 0 disables the warning
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit_warning: Option<u64>,
+    /// File holding the user key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_file: Option<PathBuf>,
+    /// Environment variable holding the user key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_env: Option<String>,
+    /// Command printing the user key on its first line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_command: Option<Vec<String>>,
+    /// File holding the application token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_file: Option<PathBuf>,
+    /// Environment variable holding the application token
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+    /// Command printing the application token on its first line
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub token_command: Option<Vec<String>>,
+}
+
+fn is_false(value: &bool) -> bool {
+    !value
+}
+
+impl PushoverConfig {
+    /// Reads `user` and `token` from their file, environment variable or
+    /// command, where one is configured.
+    pub fn resolve_secrets(&mut self) -> Result<(), Error> {
+        let user = SecretSources {
+            name: "user",
+            value: None,
+            file: self.user_file.as_deref(),
+            env: self.user_env.as_deref(),
+            command: self.user_command.as_deref(),
+        }
+        .resolve()?;
+        let token = SecretSources {
+            name: "token",
+            value: None,
+            file: self.token_file.as_deref(),
+            env: self.token_env.as_deref(),
+            command: self.token_command.as_deref(),
+        }
+        .resolve()?;
+
+        if let Some(user) = user {
+            self.user = user;
+        }
+        if let Some(token) = token {
+            self.token = token;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Deserialize)]
+struct RawPushoverConfig {
+    user: Option<String>,
+    token: Option<String>,
+    default_title: Option<String>,
+    api_url: Option<String>,
+    ca_file: Option<PathBuf>,
+    #[serde(default)]
+    strict_permissions: bool,
+    #[serde(default)]
+    validate_on_load: bool,
+    limit_warning: Option<u64>,
+    user_file: Option<PathBuf>,
+    user_env: Option<String>,
+    user_command: Option<Vec<String>>,
+    token_file: Option<PathBuf>,
+    token_env: Option<String>,
+    token_command: Option<Vec<String>>,
+}
+
+impl TryFrom<RawPushoverConfig> for PushoverConfig {
+    type Error = String;
+
+    fn try_from(raw: RawPushoverConfig) -> Result<Self, String> {
+        let credentials = [
+            SecretSources {
+                name: "user",
+                value: raw.user.as_deref(),
+                file: raw.user_file.as_deref(),
+                env: raw.user_env.as_deref(),
+                command: raw.user_command.as_deref(),
+            },
+            SecretSources {
+                name: "token",
+                value: raw.token.as_deref(),
+                file: raw.token_file.as_deref(),
+                env: raw.token_env.as_deref(),
+                command: raw.token_command.as_deref(),
+            },
+        ];
+        for sources in &credentials {
+            if sources.is_empty() {
+                return Err(format!(
+                    "missing field `{0}` (or `{0}_file`, `{0}_env`, `{0}_command`)",
+                    sources.name
+                ));
+            }
+            sources.check()?;
+        }
+
+        Ok(PushoverConfig {
+            user: raw.user.unwrap_or_default(),
+            token: raw.token.unwrap_or_default(),
+            default_title: raw.default_title,
+            api_url: raw.api_url,
+            ca_file: raw.ca_file,
+            strict_permissions: raw.strict_permissions,
+            validate_on_load: raw.validate_on_load,
+            limit_warning: raw.limit_warning,
+            user_file: raw.user_file,
+            user_env: raw.user_env,
+            user_command: raw.user_command,
+            token_file: raw.token_file,
+            token_env: raw.token_env,
+            token_command: raw.token_command,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct NotificationConfig {
+    #[serde(default)]
+    pub sound: Option<String>,
+    /// Device to deliver to, or several separated by commas; the config file
+    /// may also give a list
+    #[serde(default, deserialize_with = "device_list")]
+    pub device: Option<String>,
+    /// Default retry interval in seconds for emergency-priority messages
+    #[serde(default)]
+    pub retry: Option<u32>,
+    /// Default expiry in seconds for emergency-priority messages
+    #[serde(default)]
+    pub expire: Option<u32>,
+    /// Default acknowledgement callback URL for emergency-priority messages
+    #[serde(default)]
+    pub callback: Option<String>,
+    /// Marker appended when the CLI truncates a long message body
+    #[serde(default)]
+    pub truncation_marker: Option<String>,
+    /// Priority the CLI uses when `-p` is not given
+    #[serde(default)]
+    pub priority: Option<i8>,
+}
+
+/// Reads `device` as either a name or a list of names, which are joined into
+/// the comma-separated form the API expects.
+fn device_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<String>, D::Error> {
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum Devices {
+        One(String),
+        Many(Vec<String>),
+    }
+
+    Ok(
+        Option::<Devices>::deserialize(deserializer)?.map(|devices| match devices {
+            Devices::One(device) => device,
+            Devices::Many(devices) => devices.join(","),
+        }),
+    )
+}
+
+/// The `[network]` section.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct NetworkConfig {
+    /// HTTP proxy to tunnel API requests through, as
+    /// `http://[user:password@]host[:port]`; takes precedence over
+    /// `HTTPS_PROXY`
+    #[serde(default)]
+    pub proxy: Option<String>,
+    /// Seconds a whole API request may take
+    #[serde(default)]
+    pub timeout_secs: Option<u64>,
+    /// Seconds to wait for the connection to the server or proxy
+    #[serde(default)]
+    pub connect_timeout_secs: Option<u64>,
+    /// Seconds the server may stay silent during a request
+    #[serde(default)]
+    pub read_timeout_secs: Option<u64>,
+    /// How often to retry a request that failed for a transient reason
+    #[serde(default)]
+    pub retries: Option<u32>,
+}
+
+/// A `[profiles.<name>]` table; every setting it leaves out is inherited
+/// from `[pushover]` and `[notification]`.
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct ProfileConfig {
+    #[serde(default)]
+    pub user: Option<String>,
+    #[serde(default)]
+    pub token: Option<String>,
+    #[serde(default)]
+    pub default_title: Option<String>,
+    #[serde(default)]
+    pub sound: Option<String>,
+    #[serde(default, deserialize_with = "device_list")]
+    pub device: Option<String>,
+    #[serde(default)]
+    pub priority: Option<i8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_env: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub user_command: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_file: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_env: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub token_command: Option<Vec<String>>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, Default)]
+pub struct Config {
+    pub pushover: PushoverConfig,
+    #[serde(default)]
+    pub notification: Option<NotificationConfig>,
+    #[serde(default)]
+    pub network: Option<NetworkConfig>,
+    /// Named sets of overrides, selected with `--profile`
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub profiles: BTreeMap<String, ProfileConfig>,
+}
+
+/// Name that selects the top-level settings when no profile of that name is defined.
+pub const DEFAULT_PROFILE: &str = "default";
+
+/// Length of Pushover user keys, group keys and application tokens.
+pub const KEY_LEN: usize = 30;
+
+impl Config {
+    /// Returns the configuration with the overrides of profile `name` applied.
+    ///
+    /// The plain `[pushover]` and `[notification]` sections act as the
+    /// [`DEFAULT_PROFILE`], unless a profile of that name is defined.
+    pub fn with_profile(mut self, name: &str) -> Result<Config, Error> {
+        let profile = match self.profiles.get(name) {
+            Some(profile) => profile.clone(),
+            None if name == DEFAULT_PROFILE => return Ok(self),
+            None => {
+                let available = if self.profiles.is_empty() {
+                    "none are defined".to_string()
+                } else {
+                    let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                    format!("available: {}", names.join(", "))
+                };
+                return Err(Error::Config(format!(
+                    "Unknown profile '{}' ({})",
+                    name, available
+                )));
+            }
+        };
+
+        // Profile credentials are only read when the profile is used
+        let in_profile = |e: Error| Error::Config(format!("profile '{}': {}", name, e));
+        let user = SecretSources {
+            name: "user",
+            value: profile.user.as_deref(),
+            file: profile.user_file.as_deref(),
+            env: profile.user_env.as_deref(),
+            command: profile.user_command.as_deref(),
+        }
+        .resolve()
+        .map_err(in_profile)?;
+        let token = SecretSources {
+            name: "token",
+            value: profile.token.as_deref(),
+            file: profile.token_file.as_deref(),
+            env: profile.token_env.as_deref(),
+            command: profile.token_command.as_deref(),
+        }
+        .resolve()
+        .map_err(in_profile)?;
+
+        if let Some(user) = user {
+            self.pushover.user = user;
+        }
+        if let Some(token) = token {
+            self.pushover.token = token;
+        }
+        if profile.default_title.is_some() {
+            self.pushover.default_title = profile.default_title;
+        }
+        let notification = self.notification.get_or_insert_with(Default::default);
+        if profile.sound.is_some() {
+            notification.sound = profile.sound;
+        }
+        if profile.device.is_some() {
+            notification.device = profile.device;
+        }
+        if profile.priority.is_some() {
+            notification.priority = profile.priority;
+        }
+        Ok(self)
+    }
+
+    /// Checks the user key and token, and those given in plain text in
+    /// profiles, for leftover `__PLACEHOLDER__` values and for the format
+    /// Pushover uses: [`KEY_LEN`] letters and digits.
+    ///
+    /// Every problem found is listed in the [`Error::Config`] message.
+    pub fn validate(&self) -> Result<(), Error> {
+        let mut problems = Vec::new();
+        problems.extend(key_problem("user", &self.pushover.user));
+        problems.extend(key_problem("token", &self.pushover.token));
+        for (name, profile) in &self.profiles {
+            for (key, value) in [("user", &profile.user), ("token", &profile.token)] {
+                if let Some(value) = value {
+                    problems.extend(key_problem(&format!("profiles.{}.{}", name, key), value));
+                }
+            }
+        }
+
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(Error::Config(problems.join("; ")))
+        }
+    }
+
+    /// Returns a copy with the user keys, tokens and proxy password masked,
+    /// for display.
+    pub fn redacted(&self) -> Config {
+        let mut config = self.clone();
+        config.pushover.user = redact(&config.pushover.user);
+        config.pushover.token = redact(&config.pushover.token);
+        if let Some(network) = &mut config.network {
+            network.proxy = network.proxy.as_deref().map(redact_proxy_password);
+        }
+        for profile in config.profiles.values_mut() {
+            profile.user = profile.user.as_deref().map(redact);
+            profile.token = profile.token.as_deref().map(redact);
+        }
+        config
+    }
+}
+
+fn key_problem(name: &str, value: &str) -> Option<String> {
+    if is_placeholder(value) {
+        Some(format!("{} is still the placeholder {}", name, value))
+    } else if value.chars().count() != KEY_LEN {
+        Some(format!(
+            "{} must be {} characters long, not {}",
+            name,
+            KEY_LEN,
+            value.chars().count()
+        ))
+    } else if !value.chars().all(|c| c.is_ascii_alphanumeric()) {
+        Some(format!("{} may only contain letters and digits", name))
+    } else {
+        None
+    }
+}
+
+/// Keeps the first four characters of a key so it can still be recognised.
+/// Placeholders are not secret and are left alone.
+fn redact(value: &str) -> String {
+    if value.is_empty() || is_placeholder(value) {
+        return value.to_string();
+    }
+    if value.chars().count() < 2 * 4 {
+        return "…".to_string();
+    }
+    let prefix: String = value.chars().take(4).collect();
+    format!("{}…", prefix)
+}
+
+/// Masks the password of a proxy URL's `user:password@`.
+fn redact_proxy_password(url: &str) -> String {
+    let (scheme, rest) = url.split_once("://").unwrap_or(("", url));
+    let authority_end = rest.find('/').unwrap_or(rest.len());
+    let Some(at) = rest[..authority_end].rfind('@') else {
+        return url.to_string();
+    };
+    let Some((user, _)) = rest[..at].split_once(':') else {
+        return url.to_string();
+    };
+    let scheme = if scheme.is_empty() {
+        String::new()
+    } else {
+        format!("{}://", scheme)
+    };
+    format!("{}{}:…{}", scheme, user, &rest[at..])
+}
+
+/// A configuration together with the files it was assembled from.
+#[derive(Debug, Clone)]
+pub struct LoadedConfig {
+    pub config: Config,
+    /// Files that were read, lowest precedence first.
+    pub files: Vec<PathBuf>,
+    /// Environment variables that overrode settings of the files.
+    pub environment: Vec<String>,
+    /// Permission problems found in those files; only possible when
+    /// `strict_permissions` is off.
+    pub permission_problems: Vec<PermissionProblem>,
+}
+
+/// A config file that other users could read or change.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PermissionProblem {
+    pub path: PathBuf,
+    /// What is wrong, e.g. "is writable by others (mode 666)"
+    pub problem: String,
+    /// Shell command that fixes it
+    pub fix: String,
+}
+
+impl fmt::Display for PermissionProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.path.display(), self.problem)
+    }
+}
+
+/// Per-user configuration files in search order:
+/// `$XDG_CONFIG_HOME/pushover/config.toml`, then `~/.config/pushover/config.toml`.
+pub fn user_config_paths() -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(config_home) = env::var_os("XDG_CONFIG_HOME").filter(|dir| !dir.is_empty()) {
+        paths.push(PathBuf::from(config_home).join("pushover/config.toml"));
+    }
+    if let Some(home) = env::var_os("HOME").filter(|dir| !dir.is_empty()) {
+        let path = PathBuf::from(home).join(".config/pushover/config.toml");
+        if !paths.contains(&path) {
+            paths.push(path);
+        }
+    }
+    paths
+}
+
+/// Loads the configuration from the standard locations, see [`load_config_from`].
+pub fn load_config() -> Result<Config, Error> {
+    load_config_from(None).map(|loaded| loaded.config)
+}
+
+/// Loads the configuration, from `explicit` or `$PUSHOVER_CONFIG` alone when
+/// either is given.
+///
+/// Otherwise the first existing user file (see [`user_config_paths`]) is
+/// layered over the system file. Tables are merged key by key, so the user
+/// file only needs the settings it changes. The development fallback is only
+/// read when neither exists. The [`ENVIRONMENT_OVERRIDES`] are layered over the files in the
+/// same way; with `PUSHOVER_USER` or `PUSHOVER_TOKEN` set, no file is needed.
+///
+/// Files that others could read or change (see [`check_permissions`]) are
+/// reported in [`LoadedConfig::permission_problems`], or refused with
+/// [`Error::Config`] when `strict_permissions` is set. Strict mode applies
+/// if any file read, or the system file when it is not read, sets it; a
+/// file cannot turn it off.
+///
+/// The `*_file` and `*_command` settings are refused with [`Error::Config`]
+/// in the development fallback and in files that someone besides root and
+/// the current user could change, before any file is read or command run.
+pub fn load_config_from(explicit: Option<&Path>) -> Result<LoadedConfig, Error> {
+    let (overrides, environment) = environment_overrides()?;
+    let credentials_from_environment = environment
+        .iter()
+        .any(|name| name == "PUSHOVER_USER" || name == "PUSHOVER_TOKEN");
+    let files = match config_files(explicit) {
+        Ok(files) => files,
+        Err(_) if credentials_from_environment => Vec::new(),
+        Err(Error::Config(message)) => {
+            return Err(Error::Config(format!(
+                "{}, and PUSHOVER_USER and PUSHOVER_TOKEN are not set",
+                message
+            )))
+        }
+        Err(e) => return Err(e),
+    };
+
+    // Strict mode is sticky: a file given instead of the system file cannot
+    // switch it off
+    let system = Path::new(SYSTEM_CONFIG_PATH);
+    let system_strict = !files.iter().any(|path| path == system)
+        && read_config_value(system).is_ok_and(|value| sets_strict_permissions(&value));
+    let fallback = explicit_config_path(explicit).is_none()
+        && files
+            .iter()
+            .any(|path| path == Path::new(LOCAL_CONFIG_PATH));
+    load_files(files, system_strict, fallback, overrides, environment)
+}
+
+/// Layers `files` and then `overrides`, the environment variables named in
+/// `environment`, into a configuration. `strict` turns on strict mode
+/// whether or not a file sets it; `fallback` marks `files` as the
+/// development fallback.
+fn load_files(
+    files: Vec<PathBuf>,
+    mut strict: bool,
+    fallback: bool,
+    overrides: toml::Value,
+    environment: Vec<String>,
+) -> Result<LoadedConfig, Error> {
+    let mut merged = toml::Value::Table(toml::Table::new());
+    let mut permission_problems = Vec::new();
+    let mut refused_sources = Vec::new();
+    for path in &files {
+        let value = read_config_value(path)?;
+        strict |= sets_strict_permissions(&value);
+        permission_problems.extend(file_permission_problems(path, &value)?);
+        refused_sources.extend(secret_source_refusal(path, &value, fallback)?);
+        clear_replaced_credentials(&mut merged, &value);
+        merge_values(&mut merged, value);
+    }
+    clear_replaced_credentials(&mut merged, &overrides);
+    merge_values(&mut merged, overrides);
+
+    let mut config: Config = merged.try_into().map_err(|e| {
+        let source = if files.is_empty() {
+            "the environment".to_string()
+        } else {
+            join_paths(&files)
+        };
+        Error::Config(format!("Invalid configuration in {}: {}", source, e))
+    })?;
+
+    config.pushover.strict_permissions = strict;
+
+    // Both refusals come before resolve_secrets reads a file or runs a
+    // command that others may have put there
+    if strict && !permission_problems.is_empty() {
+        let problems: Vec<String> = permission_problems
+            .iter()
+            .map(|problem| format!("{} (fix with: {})", problem, problem.fix))
+            .collect();
+        return Err(Error::Config(format!(
+            "Refusing to load configuration because strict_permissions is set: {}",
+            problems.join("; ")
+        )));
+    }
+
+    if !refused_sources.is_empty() {
+        return Err(Error::Config(refused_sources.join("; ")));
+    }
+    config.pushover.resolve_secrets()?;
+
+    Ok(LoadedConfig {
+        config,
+        files,
+        environment,
+        permission_problems,
+    })
+}
+
+/// Environment variables that override a setting of the config files, with
+/// the section and key of the setting.
+pub const ENVIRONMENT_OVERRIDES: [(&str, &str, &str); 8] = [
+    ("PUSHOVER_USER", "pushover", "user"),
+    ("PUSHOVER_TOKEN", "pushover", "token"),
+    ("PUSHOVER_DEFAULT_TITLE", "pushover", "default_title"),
+    ("PUSHOVER_API_URL", "pushover", "api_url"),
+    ("PUSHOVER_CA_FILE", "pushover", "ca_file"),
+    ("PUSHOVER_SOUND", "notification", "sound"),
+    ("PUSHOVER_DEVICE", "notification", "device"),
+    ("PUSHOVER_PRIORITY", "notification", "priority"),
+];
+
+/// The settings given by non-empty [`ENVIRONMENT_OVERRIDES`], as a TOML value
+/// to layer over the config files, and the names of the variables used.
+fn environment_overrides() -> Result<(toml::Value, Vec<String>), Error> {
+    let mut overrides = toml::Table::new();
+    let mut names = Vec::new();
+    for (name, section, key) in ENVIRONMENT_OVERRIDES {
+        let Some(value) = env::var(name).ok().filter(|value| !value.is_empty()) else {
+            continue;
+        };
+        let value = if key == "priority" {
+            let priority = value.trim().parse::<i8>().map_err(|_| {
+                Error::Config(format!("{} must be a number, not '{}'", name, value))
+            })?;
+            toml::Value::Integer(priority.into())
+        } else {
+            toml::Value::String(value)
+        };
+        if let toml::Value::Table(table) = overrides
+            .entry(section)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+        {
+            table.insert(key.to_string(), value);
+        }
+        names.push(name.to_string());
+    }
+    Ok((toml::Value::Table(overrides), names))
+}
+
+/// The config files [`load_config_from`] reads, lowest precedence first.
+pub fn config_files(explicit: Option<&Path>) -> Result<Vec<PathBuf>, Error> {
+    if let Some(path) = explicit_config_path(explicit) {
+        return Ok(vec![path]);
+    }
+
+    let system = Some(PathBuf::from(SYSTEM_CONFIG_PATH)).filter(|path| path.is_file());
+    let user = user_config_paths().into_iter().find(|path| path.is_file());
+    let files: Vec<PathBuf> = system.into_iter().chain(user).collect();
+    if !files.is_empty() {
+        return Ok(files);
+    }
+
+    // The development fallback is never layered under a user file, so a
+    // checked-out directory cannot redirect the user's credentials
+    let local = PathBuf::from(LOCAL_CONFIG_PATH);
+    if local.is_file() {
+        return Ok(vec![local]);
+    }
+    Err(Error::Config(format!(
+        "Config file not found. Tried {}",
+        join_paths(&config_search_paths(None))
+    )))
+}
+
+/// Whether a config file sets `strict_permissions = true` in `[pushover]`.
+fn sets_strict_permissions(value: &toml::Value) -> bool {
+    value
+        .get("pushover")
+        .and_then(|pushover| pushover.get("strict_permissions"))
+        .and_then(toml::Value::as_bool)
+        .unwrap_or(false)
+}
+
+/// Every file [`load_config_from`] looks for, highest precedence first,
+/// whether or not it exists.
+pub fn config_search_paths(explicit: Option<&Path>) -> Vec<PathBuf> {
+    if let Some(path) = explicit_config_path(explicit) {
+        return vec![path];
+    }
+
+    let mut paths = user_config_paths();
+    paths.push(PathBuf::from(SYSTEM_CONFIG_PATH));
+    paths.push(PathBuf::from(LOCAL_CONFIG_PATH));
+    paths
+}
+
+/// `explicit`, or else `$PUSHOVER_CONFIG`.
+fn explicit_config_path(explicit: Option<&Path>) -> Option<PathBuf> {
+    explicit.map(Path::to_path_buf).or_else(|| {
+        env::var_os("PUSHOVER_CONFIG")
+            .filter(|path| !path.is_empty())
+            .map(PathBuf::from)
+    })
+}
+
+/// Checks who besides the current user and root can read or change the
+/// config file at `path`.
+///
+/// Any file writable by others is a problem, since it can name a
+/// `token_command` to run. Being readable by others is only a problem when
+/// the file holds the user key or token in plain text. Always empty on
+/// platforms other than Unix.
+pub fn check_permissions(path: &Path) -> Result<Vec<PermissionProblem>, Error> {
+    let value = read_config_value(path)?;
+    file_permission_problems(path, &value)
+}
+
+fn read_config_value(path: &Path) -> Result<toml::Value, Error> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| Error::Config(format!("Cannot read config file {}: {}", path.display(), e)))?;
+    toml::from_str(&content).map_err(|e| {
+        Error::Config(format!(
+            "Invalid TOML in config file {}: {}",
+            path.display(),
+            e
+        ))
+    })
+}
+
+#[cfg(unix)]
+fn file_permission_problems(
+    path: &Path,
+    value: &toml::Value,
+) -> Result<Vec<PermissionProblem>, Error> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path)
+        .map_err(|e| Error::Config(format!("Cannot read config file {}: {}", path.display(), e)))?;
+    let mode = metadata.mode() & 0o777;
+    let problem = |problem: String, fix: String| PermissionProblem {
+        path: path.to_path_buf(),
+        problem,
+        fix,
+    };
+
+    let mut problems = changeable_by_others(path)?;
+    if mode & 0o044 != 0 && holds_credentials(value) {
+        problems.push(problem(
+            format!(
+                "holds credentials but is readable by {} (mode {:03o})",
+                permission_classes(mode & 0o040 != 0, mode & 0o004 != 0),
+                mode
+            ),
+            format!("chmod go-r {}", path.display()),
+        ));
+    }
+    Ok(problems)
+}
+
+/// The ways someone besides the current user and root could change the
+/// file at `path`: by owning it or by being allowed to write to it.
+#[cfg(unix)]
+fn changeable_by_others(path: &Path) -> Result<Vec<PermissionProblem>, Error> {
+    use std::os::unix::fs::MetadataExt;
+
+    let metadata = fs::metadata(path)
+        .map_err(|e| Error::Config(format!("Cannot read config file {}: {}", path.display(), e)))?;
+    let mode = metadata.mode() & 0o777;
+    let problem = |problem: String, fix: String| PermissionProblem {
+        path: path.to_path_buf(),
+        problem,
+        fix,
+    };
+
+    let mut problems = Vec::new();
+    // SAFETY: geteuid has no preconditions and cannot fail
+    let uid = unsafe { libc::geteuid() };
+    if metadata.uid() != uid && metadata.uid() != 0 {
+        problems.push(problem(
+            format!("is owned by another user (uid {})", metadata.uid()),
+            format!("chown {} {}", uid, path.display()),
+        ));
+    }
+    if mode & 0o022 != 0 {
+        problems.push(problem(
+            format!(
+                "is writable by {} (mode {:03o})",
+                permission_classes(mode & 0o020 != 0, mode & 0o002 != 0),
+                mode
+            ),
+            format!("chmod go-w {}", path.display()),
+        ));
+    }
+    Ok(problems)
+}
+
+#[cfg(not(unix))]
+fn changeable_by_others(_path: &Path) -> Result<Vec<PermissionProblem>, Error> {
+    Ok(Vec::new())
+}
+
+#[cfg(not(unix))]
+fn file_permission_problems(
+    _path: &Path,
+    _value: &toml::Value,
+) -> Result<Vec<PermissionProblem>, Error> {
+    Ok(Vec::new())
+}
+
+#[cfg(unix)]
+fn permission_classes(group: bool, others: bool) -> &'static str {
+    match (group, others) {
+        (true, true) => "its group and others",
+        (true, false) => "its group",
+        _ => "others",
+    }
+}
+
+/// Why the `*_file` and `*_command` settings of the config file at `path`,
+/// which read a file or run a command, must not be used, if they must not.
+///
+/// They are only used from the system file, a user file or one given with
+/// `--config` or `$PUSHOVER_CONFIG`, never from the development fallback,
+/// and only while no one but root and the current user can change the file.
+fn secret_source_refusal(
+    path: &Path,
+    value: &toml::Value,
+    fallback: bool,
+) -> Result<Option<String>, Error> {
+    let settings = secret_source_settings(value);
+    if settings.is_empty() {
+        return Ok(None);
+    }
+    let reason = if fallback {
+        "only the system and user config files may read files or run commands".to_string()
+    } else {
+        let problems = changeable_by_others(path)?;
+        if problems.is_empty() {
+            return Ok(None);
+        }
+        let problems: Vec<String> = problems
+            .iter()
+            .map(|problem| format!("{} (fix with: {})", problem.problem, problem.fix))
+            .collect();
+        format!("it {}", problems.join(" and "))
+    };
+    Ok(Some(format!(
+        "Refusing to use {} from {}: {}",
+        settings.join(", "),
+        path.display(),
+        reason
+    )))
+}
+
+/// The `*_file` and `*_command` settings in `[pushover]` and the profiles of
+/// `value`, named as in `profiles.backups.token_command`.
+fn secret_source_settings(value: &toml::Value) -> Vec<String> {
+    let profiles = value
+        .get("profiles")
+        .and_then(toml::Value::as_table)
+        .into_iter()
+        .flat_map(|profiles| profiles.iter())
+        .map(|(name, profile)| (format!("profiles.{}.", name), profile));
+    let tables = value
+        .get("pushover")
+        .map(|pushover| (String::new(), pushover))
+        .into_iter()
+        .chain(profiles);
+
+    let mut settings = Vec::new();
+    for (prefix, table) in tables {
+        for key in ["user_file", "user_command", "token_file", "token_command"] {
+            if table.get(key).is_some() {
+                settings.push(format!("{}{}", prefix, key));
+            }
+        }
+    }
+    settings
+}
+
+/// Whether `value` gives a user key or token in plain text, in `[pushover]`
+/// or a profile. The `__PLACEHOLDER__` values of the example config do not
+/// count.
+fn holds_credentials(value: &toml::Value) -> bool {
+    let profiles = value
+        .get("profiles")
+        .and_then(toml::Value::as_table)
+        .into_iter()
+        .flat_map(|profiles| profiles.values());
+    value
+        .get("pushover")
+        .into_iter()
+        .chain(profiles)
+        .any(|table| {
+            ["user", "token"].iter().any(|key| {
+                table
+                    .get(key)
+                    .and_then(toml::Value::as_str)
+                    .is_some_and(|value| !is_placeholder(value))
+            })
+        })
+}
+
+fn is_placeholder(value: &str) -> bool {
+    value.len() > 4 && value.starts_with("__") && value.ends_with("__")
+}
+
+/// Settings that each name the source of one credential.
+const CREDENTIAL_KEYS: [[&str; 4]; 2] = [
+    ["user", "user_file", "user_env", "user_command"],
+    ["token", "token_file", "token_env", "token_command"],
+];
+
+/// Drops credentials from `base` that `overlay` gives in any form, so that,
+/// say, a `token_file` in the user config replaces a system-wide `token`
+/// instead of conflicting with it.
+fn clear_replaced_credentials(base: &mut toml::Value, overlay: &toml::Value) {
+    clear_credentials_in(base.get_mut("pushover"), overlay.get("pushover"));
+    if let (Some(base_profiles), Some(toml::Value::Table(profiles))) =
+        (base.get_mut("profiles"), overlay.get("profiles"))
+    {
+        for (name, profile) in profiles {
+            clear_credentials_in(base_profiles.get_mut(name), Some(profile));
+        }
+    }
+}
+
+fn clear_credentials_in(base: Option<&mut toml::Value>, overlay: Option<&toml::Value>) {
+    let (Some(toml::Value::Table(base)), Some(toml::Value::Table(overlay))) = (base, overlay)
+    else {
+        return;
+    };
+    for keys in CREDENTIAL_KEYS {
+        if keys.iter().any(|key| overlay.contains_key(*key)) {
+            for key in keys {
+                base.remove(key);
+            }
+        }
+    }
+}
+
+/// Overlays `overlay` onto `base`. Tables are merged key by key; any other
+/// value replaces what was there.
+fn merge_values(base: &mut toml::Value, overlay: toml::Value) {
+    match (base, overlay) {
+        (toml::Value::Table(base), toml::Value::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+fn join_paths(paths: &[PathBuf]) -> String {
+    paths
+        .iter()
+        .map(|path| path.display().to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_config_parsing() {
+        let config_content = r#"
+[pushover]
+user = "test_user_key"
+token = "test_app_token"
+default_title = "Test Title"
+
+[notification]
+sound = "pushover"
+device = "iphone"
+"#;
+
+        let config: Config = toml::from_str(config_content).unwrap();
+        assert_eq!(config.pushover.user, "test_user_key");
+        assert_eq!(config.pushover.token, "test_app_token");
+        assert_eq!(
+            config.pushover.default_title,
+            Some("Test Title".to_string())
+        );
+        assert!(config.notification.is_some());
+        let notification = config.notification.unwrap();
+        assert_eq!(notification.sound, Some("pushover".to_string()));
+        assert_eq!(notification.device, Some("iphone".to_string()));
+    }
+
+    #[test]
+    fn test_notification_config_defaults() {
+        let config = NotificationConfig::default();
+        assert!(config.sound.is_none());
+        assert!(config.device.is_none());
+    }
+
+    #[test]
+    fn test_with_profile() {
+        let config: Config = toml::from_str(
+            r#"
+[pushover]
+user = "team_user"
+token = "default_token"
+default_title = "Server"
+
+[notification]
+sound = "pushover"
+
+[profiles.backups]
+token = "backup_token"
+priority = -1
+
+[profiles.security]
+user = "security_group"
+token = "security_token"
+sound = "siren"
+priority = 1
+"#,
+        )
+        .unwrap();
+
+        let backups = config.clone().with_profile("backups").unwrap();
+        assert_eq!(backups.pushover.user, "team_user");
+        assert_eq!(backups.pushover.token, "backup_token");
+        assert_eq!(backups.pushover.default_title, Some("Server".to_string()));
+        let notification = backups.notification.unwrap();
+        assert_eq!(notification.sound, Some("pushover".to_string()));
+        assert_eq!(notification.priority, Some(-1));
+
+        let security = config.clone().with_profile("security").unwrap();
+        assert_eq!(security.pushover.user, "security_group");
+        assert_eq!(
+            security.notification.unwrap().sound,
+            Some("siren".to_string())
+        );
+
+        let default = config.clone().with_profile(DEFAULT_PROFILE).unwrap();
+        assert_eq!(default.pushover.token, "default_token");
+
+        let err = config.with_profile("deploys").unwrap_err();
+        assert!(matches!(err, Error::Config(_)));
+        assert_eq!(
+            err.to_string(),
+            "Unknown profile 'deploys' (available: backups, security)"
+        );
+    }
+
+    #[test]
+    fn test_credential_replaced_in_any_form() {
+        let mut base: toml::Value = toml::from_str(
+            r#"
+[pushover]
+user = "system_user"
+token = "system_token"
+
+[profiles.backups]
+token_env = "BACKUP_TOKEN"
+"#,
+        )
+        .unwrap();
+        let overlay: toml::Value = toml::from_str(
+            r#"
+[pushover]
+token_file = "/run/secrets/pushover"
+
+[profiles.backups]
+token = "user_backup_token"
+"#,
+        )
+        .unwrap();
+        clear_replaced_credentials(&mut base, &overlay);
+        merge_values(&mut base, overlay);
+
+        let config: Config = base.try_into().unwrap();
+        assert_eq!(config.pushover.user, "system_user");
+        assert_eq!(config.pushover.token, "");
+        assert_eq!(
+            config.pushover.token_file,
+            Some(PathBuf::from("/run/secrets/pushover"))
+        );
+        let backups = &config.profiles["backups"];
+        assert_eq!(backups.token, Some("user_backup_token".to_string()));
+        assert!(backups.token_env.is_none());
+    }
+
+    #[test]
+    fn test_merge_values() {
+        let mut base: toml::Value = toml::from_str(
+            r#"
+[pushover]
+user = "system_user"
+token = "system_token"
+default_title = "System"
+
+[notification]
+sound = "pushover"
+"#,
+        )
+        .unwrap();
+        let overlay: toml::Value = toml::from_str(
+            r#"
+[pushover]
+token = "user_token"
+
+[notification]
+device = "iphone"
+"#,
+        )
+        .unwrap();
+        merge_values(&mut base, overlay);
+
+        let config: Config = base.try_into().unwrap();
+        assert_eq!(config.pushover.user, "system_user");
+        assert_eq!(config.pushover.token, "user_token");
+        assert_eq!(config.pushover.default_title, Some("System".to_string()));
+        let notification = config.notification.unwrap();
+        assert_eq!(notification.sound, Some("pushover".to_string()));
+        assert_eq!(notification.device, Some("iphone".to_string()));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_strict_permissions_cannot_be_turned_off() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let write = |name: &str, content: &str, mode: u32| {
+            let path = dir.path().join(name);
+            fs::write(&path, content).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+            path
+        };
+        let system = write(
+            "system.toml",
+            "[pushover]\nuser = \"uQiRzpo4DXghDmr9QzzfQu27cmVRsG\"\nstrict_permissions = true\n",
+            0o600,
+        );
+        let user = write(
+            "user.toml",
+            concat!(
+                "[pushover]\nuser = \"uQiRzpo4DXghDmr9QzzfQu27cmVRsG\"\n",
+                "token = \"azGDORePK8gMaC0QOYAMyEEuzJnyUi\"\nstrict_permissions = false\n",
+            ),
+            0o666,
+        );
+        let no_overrides = || toml::Value::Table(toml::Table::new());
+
+        // Neither a user file layered over the system file nor one read
+        // instead of it can switch strict mode off
+        for (files, system_strict) in [
+            (vec![system, user.clone()], false),
+            (vec![user.clone()], true),
+        ] {
+            let err =
+                load_files(files, system_strict, false, no_overrides(), Vec::new()).unwrap_err();
+            let message = err.to_string();
+            assert!(message
+                .contains("Refusing to load configuration because strict_permissions is set"));
+            assert!(message.contains("is writable by its group and others (mode 666)"));
+        }
+
+        let loaded = load_files(vec![user], false, false, no_overrides(), Vec::new()).unwrap();
+        assert!(!loaded.config.pushover.strict_permissions);
+        assert!(!loaded.permission_problems.is_empty());
+    }
+
+    #[test]
+    fn test_holds_credentials() {
+        let value = |content: &str| toml::from_str::<toml::Value>(content).unwrap();
+        assert!(holds_credentials(&value(
+            "[pushover]\nuser = \"uQiRzpo4DXghDmr9QzzfQu27cmVRsG\"\n"
+        )));
+        assert!(holds_credentials(&value(
+            "[profiles.backups]\ntoken = \"azGDORePK8gMaC0QOYAMyEEuzJnyUi\"\n"
+        )));
+        assert!(!holds_credentials(&value(
+            "[pushover]\nuser = \"__YOUR_USER_KEY__\"\ntoken = \"__YOUR_APP_TOKEN__\"\n"
+        )));
+        assert!(!holds_credentials(&value(
+            "[pushover]\ntoken_file = \"/run/secrets/pushover\"\n"
+        )));
+    }
+}
@@ -0,0 +1,130 @@
+use std::fmt;
+use std::io;
+
+use crate::ApiError;
+
+/// Errors returned by the library, grouped by what the caller can do about them.
+#[derive(Debug)]
+pub enum Error {
+    /// The configuration is missing, unreadable or invalid.
+    Config(String),
+    /// A file or network operation failed.
+    Io(io::Error),
+    /// TLS could not be set up, or the server's certificate was not trusted.
+    Tls(String),
+    /// The server answered with something other than a Pushover JSON response.
+    Http { status: u16, body: String },
+    /// Pushover rejected the request.
+    Api(ApiError),
+    /// Pushover rejected the request because the app is over its message
+    /// limit (HTTP 429).
+    RateLimited(ApiError),
+    /// A message or argument failed local validation; nothing was sent.
+    Validation(String),
+    /// The server or proxy did not answer within the client's
+    /// [`Timeouts`](crate::Timeouts); the message may or may not have been
+    /// delivered.
+    Timeout(String),
+}
+
+impl Error {
+    /// An I/O error with the file or operation it concerns prefixed.
+    pub(crate) fn io(context: impl fmt::Display, error: io::Error) -> Self {
+        Error::Io(io::Error::new(
+            error.kind(),
+            format!("{}: {}", context, error),
+        ))
+    }
+
+    /// A response that could not be understood.
+    pub(crate) fn invalid_data(message: impl Into<String>) -> Self {
+        Error::Io(io::Error::new(io::ErrorKind::InvalidData, message.into()))
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Config(message)
+            | Error::Tls(message)
+            | Error::Validation(message)
+            | Error::Timeout(message) => f.write_str(message),
+            Error::Io(error) => error.fmt(f),
+            Error::Http { status, body } => {
+                write!(f, "HTTP request failed with status {}: {}", status, body)
+            }
+            Error::Api(error) | Error::RateLimited(error) => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io(error) => Some(error),
+            Error::Api(error) | Error::RateLimited(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for Error {
+    fn from(error: io::Error) -> Self {
+        // rustls reports handshake failures through the stream's io::Error
+        match error
+            .get_ref()
+            .and_then(|inner| inner.downcast_ref::<rustls::Error>())
+        {
+            Some(tls_error) => Error::from(tls_error.clone()),
+            None => Error::Io(error),
+        }
+    }
+}
+
+impl From<rustls::Error> for Error {
+    fn from(error: rustls::Error) -> Self {
+        Error::Tls(format!("TLS error: {}", error))
+    }
+}
+
+impl From<ApiError> for Error {
+    fn from(error: ApiError) -> Self {
+        if error.http_status == 429 {
+            Error::RateLimited(error)
+        } else {
+            Error::Api(error)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_error_classification() {
+        let api_error = |http_status| ApiError {
+            http_status,
+            request: None,
+            errors: vec!["message limit reached".to_string()],
+        };
+        assert!(matches!(Error::from(api_error(400)), Error::Api(_)));
+        assert!(matches!(Error::from(api_error(429)), Error::RateLimited(_)));
+
+        let handshake = io::Error::new(
+            io::ErrorKind::InvalidData,
+            rustls::Error::InvalidCertificate(rustls::CertificateError::UnknownIssuer),
+        );
+        assert!(matches!(Error::from(handshake), Error::Tls(_)));
+        let refused = io::Error::new(io::ErrorKind::ConnectionRefused, "refused");
+        assert!(matches!(Error::from(refused), Error::Io(_)));
+
+        let err = Error::io(
+            "Cannot read attachment a.png",
+            io::ErrorKind::NotFound.into(),
+        );
+        assert!(err
+            .to_string()
+            .starts_with("Cannot read attachment a.png: "));
+    }
+}
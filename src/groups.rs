@@ -0,0 +1,206 @@
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+
+use crate::message::{check_device_name, check_len};
+use crate::{url_encode, Client, Error};
+
+/// Longest memo Pushover keeps for a group member.
+pub const MAX_MEMO_LEN: usize = 200;
+
+/// A delivery group owned by the application's account.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Group {
+    /// Group key, usable wherever a user key is.
+    pub group: String,
+    pub name: String,
+}
+
+/// A delivery group and its members.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct GroupInfo {
+    pub name: String,
+    #[serde(default)]
+    pub users: Vec<GroupMember>,
+}
+
+/// A user in a delivery group.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct GroupMember {
+    pub user: String,
+    /// The only device the user receives the group's messages on, if set.
+    #[serde(default)]
+    pub device: Option<String>,
+    #[serde(default)]
+    pub memo: String,
+    /// Whether delivery to the user is paused.
+    #[serde(default)]
+    pub disabled: bool,
+}
+
+#[derive(Deserialize)]
+struct CreateResponse {
+    group: String,
+}
+
+#[derive(Deserialize)]
+struct ListResponse {
+    #[serde(default)]
+    groups: Vec<Group>,
+}
+
+impl Client {
+    /// Creates a delivery group and returns its key.
+    pub fn create_group(&self, name: &str) -> Result<String, Error> {
+        check_group_name(name)?;
+        let response: CreateResponse =
+            self.post("groups.json", vec![("name", name.to_string())])?;
+        Ok(response.group)
+    }
+
+    /// Lists the delivery groups of the application's account.
+    pub fn groups(&self) -> Result<Vec<Group>, Error> {
+        let response: ListResponse = self.get("groups.json")?;
+        Ok(response.groups)
+    }
+
+    /// Fetches the name and members of a delivery group.
+    pub fn group(&self, group: &str) -> Result<GroupInfo, Error> {
+        self.get(&group_endpoint(group, ""))
+    }
+
+    /// Adds a user to a delivery group, optionally limited to one of their
+    /// devices and with a memo shown in the group's member list.
+    pub fn add_group_user(
+        &self,
+        group: &str,
+        user: &str,
+        device: Option<&str>,
+        memo: Option<&str>,
+    ) -> Result<(), Error> {
+        let mut fields = member_fields(user, device)?;
+        if let Some(memo) = memo {
+            check_len("memo", memo, MAX_MEMO_LEN)?;
+            fields.push(("memo", memo.to_string()));
+        }
+        self.group_post(group, "add_user", fields)
+    }
+
+    /// Removes a user, or only the given device of theirs, from a delivery
+    /// group.
+    pub fn remove_group_user(
+        &self,
+        group: &str,
+        user: &str,
+        device: Option<&str>,
+    ) -> Result<(), Error> {
+        self.group_post(group, "delete_user", member_fields(user, device)?)
+    }
+
+    /// Pauses delivery of the group's messages to a user without removing
+    /// them, e.g. when they go off call.
+    pub fn disable_group_user(
+        &self,
+        group: &str,
+        user: &str,
+        device: Option<&str>,
+    ) -> Result<(), Error> {
+        self.group_post(group, "disable_user", member_fields(user, device)?)
+    }
+
+    /// Resumes delivery to a user disabled with
+    /// [`Client::disable_group_user`].
+    pub fn enable_group_user(
+        &self,
+        group: &str,
+        user: &str,
+        device: Option<&str>,
+    ) -> Result<(), Error> {
+        self.group_post(group, "enable_user", member_fields(user, device)?)
+    }
+
+    /// Renames a delivery group.
+    pub fn rename_group(&self, group: &str, name: &str) -> Result<(), Error> {
+        check_group_name(name)?;
+        self.group_post(group, "rename", vec![("name", name.to_string())])
+    }
+
+    fn group_post(
+        &self,
+        group: &str,
+        action: &str,
+        fields: Vec<(&'static str, String)>,
+    ) -> Result<(), Error> {
+        let _: IgnoredAny = self.post(&group_endpoint(group, action), fields)?;
+        Ok(())
+    }
+}
+
+/// `groups/<key>.json`, or `groups/<key>/<action>.json` for an action.
+fn group_endpoint(group: &str, action: &str) -> String {
+    if action.is_empty() {
+        format!("groups/{}.json", url_encode(group))
+    } else {
+        format!("groups/{}/{}.json", url_encode(group), action)
+    }
+}
+
+fn member_fields(user: &str, device: Option<&str>) -> Result<Vec<(&'static str, String)>, Error> {
+    let mut fields = vec![("user", user.to_string())];
+    if let Some(device) = device {
+        check_device_name(device)?;
+        fields.push(("device", device.to_string()));
+    }
+    Ok(fields)
+}
+
+fn check_group_name(name: &str) -> Result<(), Error> {
+    if name.trim().is_empty() {
+        return Err(Error::Validation("group name is empty".to_string()));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_info_deserialize() {
+        let body = r#"{
+            "status": 1,
+            "name": "On call",
+            "users": [
+                {
+                    "user": "uQiRzpo4DXghDmr9QzzfQu27cmVRsG",
+                    "device": null,
+                    "memo": "primary",
+                    "disabled": false
+                },
+                {
+                    "user": "u1vCvgyfFsoqQ4QKr4XyvsbFD2t3LR",
+                    "device": "pager",
+                    "memo": "",
+                    "disabled": true
+                }
+            ],
+            "request": "5042853c-402d-4a18-abcb-168734a801de"
+        }"#;
+
+        let info: GroupInfo = serde_json::from_str(body).unwrap();
+        assert_eq!(info.name, "On call");
+        assert_eq!(info.users.len(), 2);
+        assert_eq!(info.users[0].device, None);
+        assert_eq!(info.users[0].memo, "primary");
+        assert_eq!(info.users[1].device.as_deref(), Some("pager"));
+        assert!(info.users[1].disabled);
+    }
+
+    #[test]
+    fn test_group_endpoint() {
+        assert_eq!(group_endpoint("gKey", ""), "groups/gKey.json");
+        assert_eq!(
+            group_endpoint("gKey", "add_user"),
+            "groups/gKey/add_user.json"
+        );
+    }
+}
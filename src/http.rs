@@ -0,0 +1,360 @@
+//! Parsing of the HTTP/1.1 responses the API and proxies send.
+//!
+//! The parser works on the complete response as read from a connection
+//! closed by the server, so it can be tested and fuzzed on plain byte
+//! slices. It never panics; anything it cannot make sense of is an error.
+
+use crate::Error;
+
+/// A parsed HTTP/1.1 response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HttpResponse {
+    pub status: u16,
+    /// Reason phrase of the status line, e.g. `Not Found`; may be empty.
+    pub reason: String,
+    /// Headers in the order received, with names as sent.
+    pub headers: Vec<(String, String)>,
+    /// Body with any chunked transfer encoding removed.
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Parses a complete response: the status line, the headers and a body
+    /// delimited by `Transfer-Encoding: chunked`, `Content-Length` or the
+    /// end of the data. Interim 1xx responses before it are skipped.
+    ///
+    /// Bytes after a chunked or `Content-Length` body are ignored.
+    pub fn parse(raw: &[u8]) -> Result<HttpResponse, Error> {
+        let mut rest = raw;
+        loop {
+            let (head, after_head) = split_head(rest)?;
+            let mut lines = head.split('\n').map(|line| line.trim_end_matches('\r'));
+            let (status, reason) = parse_status_line(lines.next().unwrap_or_default())?;
+            let headers = parse_headers(lines)?;
+
+            // 101 Switching Protocols is final; other 1xx are followed by the real response
+            if (100..200).contains(&status) && status != 101 {
+                rest = after_head;
+                continue;
+            }
+
+            let body = if status == 204 || status == 304 {
+                Vec::new()
+            } else {
+                parse_body(&headers, after_head)?
+            };
+            return Ok(HttpResponse {
+                status,
+                reason: reason.to_string(),
+                headers,
+                body,
+            });
+        }
+    }
+
+    /// Returns the first header named `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+fn malformed(problem: impl std::fmt::Display) -> Error {
+    Error::invalid_data(format!("Malformed HTTP response: {}", problem))
+}
+
+/// Splits off the status line and headers, up to the empty line ending them.
+fn split_head(raw: &[u8]) -> Result<(&str, &[u8]), Error> {
+    let (end, separator) = match find(raw, b"\r\n\r\n") {
+        Some(end) => (end, 4),
+        // Tolerate servers that end lines with a bare LF
+        None => (
+            find(raw, b"\n\n").ok_or_else(|| malformed("missing header terminator"))?,
+            2,
+        ),
+    };
+    let head =
+        std::str::from_utf8(&raw[..end]).map_err(|_| malformed("headers are not valid UTF-8"))?;
+    Ok((head, &raw[end + separator..]))
+}
+
+/// Parses `HTTP/1.x <3-digit status> [reason]` into the status and reason.
+pub(crate) fn parse_status_line(line: &str) -> Result<(u16, &str), Error> {
+    let invalid = || malformed(format_args!("invalid status line '{}'", line));
+    let (version, rest) = line.split_once(' ').ok_or_else(invalid)?;
+    if !version.starts_with("HTTP/1.") {
+        return Err(invalid());
+    }
+    let (code, reason) = rest.split_once(' ').unwrap_or((rest, ""));
+    if code.len() != 3 || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    let status: u16 = code.parse().map_err(|_| invalid())?;
+    if !(100..600).contains(&status) {
+        return Err(invalid());
+    }
+    Ok((status, reason.trim()))
+}
+
+fn parse_headers<'a>(lines: impl Iterator<Item = &'a str>) -> Result<Vec<(String, String)>, Error> {
+    let mut headers: Vec<(String, String)> = Vec::new();
+    for line in lines {
+        // Obsolete line folding continues the previous header's value
+        if line.starts_with([' ', '\t']) {
+            let (_, value) = headers
+                .last_mut()
+                .ok_or_else(|| malformed("continuation line before any header"))?;
+            value.push(' ');
+            value.push_str(line.trim());
+            continue;
+        }
+        let (name, value) = line
+            .split_once(':')
+            .ok_or_else(|| malformed(format_args!("invalid header line '{}'", line)))?;
+        if name.is_empty() || name.contains(|c: char| c.is_whitespace()) {
+            return Err(malformed(format_args!("invalid header name '{}'", name)));
+        }
+        headers.push((name.to_string(), value.trim().to_string()));
+    }
+    Ok(headers)
+}
+
+fn parse_body(headers: &[(String, String)], data: &[u8]) -> Result<Vec<u8>, Error> {
+    // Only plain chunked is supported; a body also compressed with another
+    // coding must not be mistaken for the decoded one
+    let codings: Vec<&str> = header_values(headers, "Transfer-Encoding").collect();
+    if !codings.is_empty() {
+        if !matches!(codings[..], [coding] if coding.eq_ignore_ascii_case("chunked")) {
+            return Err(malformed(format_args!(
+                "unsupported transfer encoding '{}'",
+                codings.join(", ")
+            )));
+        }
+        return decode_chunked(data);
+    }
+
+    let mut lengths = header_values(headers, "Content-Length");
+    match lengths.next() {
+        Some(first) => {
+            if lengths.any(|other| other != first) {
+                return Err(malformed("conflicting Content-Length headers"));
+            }
+            let length: usize = first
+                .parse()
+                .map_err(|_| malformed(format_args!("invalid Content-Length '{}'", first)))?;
+            data.get(..length).map(<[u8]>::to_vec).ok_or_else(|| {
+                malformed(format_args!(
+                    "body is {} bytes, Content-Length says {}",
+                    data.len(),
+                    length
+                ))
+            })
+        }
+        None => Ok(data.to_vec()),
+    }
+}
+
+/// The comma-separated values of every header named `name`.
+fn header_values<'a>(
+    headers: &'a [(String, String)],
+    name: &'a str,
+) -> impl Iterator<Item = &'a str> {
+    headers
+        .iter()
+        .filter(move |(key, _)| key.eq_ignore_ascii_case(name))
+        .flat_map(|(_, value)| value.split(','))
+        .map(str::trim)
+}
+
+/// Removes the chunked transfer encoding: chunks of `<hex size>[;ext]\r\n`
+/// followed by the data and `\r\n`, ended by a zero-size chunk and optional
+/// trailers, which are dropped.
+fn decode_chunked(mut data: &[u8]) -> Result<Vec<u8>, Error> {
+    let mut body = Vec::new();
+    loop {
+        let line_end = find(data, b"\n").ok_or_else(|| malformed("truncated chunk size"))?;
+        let line = std::str::from_utf8(&data[..line_end])
+            .map_err(|_| malformed("invalid chunk size"))?
+            .trim_end_matches('\r');
+        let size = line.split(';').next().unwrap_or_default().trim();
+        let size = usize::from_str_radix(size, 16)
+            .map_err(|_| malformed(format_args!("invalid chunk size '{}'", line)))?;
+        data = &data[line_end + 1..];
+
+        if size == 0 {
+            // Trailers end with an empty line, which a server closing the
+            // connection may leave out
+            return Ok(body);
+        }
+
+        let chunk = data
+            .get(..size)
+            .ok_or_else(|| malformed("truncated chunk"))?;
+        body.extend_from_slice(chunk);
+        data = &data[size..];
+        data = data
+            .strip_prefix(b"\r\n")
+            .or_else(|| data.strip_prefix(b"\n"))
+            .ok_or_else(|| malformed("missing line break after chunk"))?;
+    }
+}
+
+fn find(haystack: &[u8], needle: &[u8]) -> Option<usize> {
+    haystack
+        .windows(needle.len())
+        .position(|window| window == needle)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_content_length() {
+        let raw = b"HTTP/1.1 400 Bad Request\r\n\
+            Content-Type: application/json\r\n\
+            Content-Length: 12\r\n\
+            X-Limit-App-Remaining: 7496\r\n\
+            \r\n\
+            {\"status\":0}trailing garbage";
+        let response = HttpResponse::parse(raw).unwrap();
+        assert_eq!(response.status, 400);
+        assert_eq!(response.reason, "Bad Request");
+        assert_eq!(response.header("content-type"), Some("application/json"));
+        assert_eq!(response.header("X-LIMIT-APP-REMAINING"), Some("7496"));
+        assert_eq!(response.header("Retry-After"), None);
+        assert_eq!(response.body, b"{\"status\":0}");
+
+        // Without a length the body runs to the end of the data
+        let response = HttpResponse::parse(b"HTTP/1.0 200 OK\r\n\r\nhello").unwrap();
+        assert_eq!(response.body, b"hello");
+    }
+
+    #[test]
+    fn test_parse_chunked() {
+        let raw = b"HTTP/1.1 200 OK\r\n\
+            Transfer-Encoding: chunked\r\n\
+            \r\n\
+            7\r\n{\"statu\r\n\
+            5;ext=1\r\ns\":1}\r\n\
+            0\r\n\
+            X-Trailer: dropped\r\n\
+            \r\n";
+        let response = HttpResponse::parse(raw).unwrap();
+        assert_eq!(response.body, b"{\"status\":1}");
+
+        // Upper-case hex sizes and a missing final empty line
+        let raw = b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nA\r\n0123456789\r\n0\r\n";
+        assert_eq!(HttpResponse::parse(raw).unwrap().body, b"0123456789");
+
+        for truncated in [
+            &b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n"[..],
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n5\r\nabc",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nabcX",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nzz\r\n",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip, chunked\r\n\r\n2\r\nab\r\n0\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: gzip\r\nTransfer-Encoding: chunked\r\n\r\n0\r\n\r\n",
+        ] {
+            assert!(HttpResponse::parse(truncated).is_err());
+        }
+    }
+
+    #[test]
+    fn test_parse_status_line() {
+        assert_eq!(parse_status_line("HTTP/1.1 200 OK").unwrap(), (200, "OK"));
+        assert_eq!(parse_status_line("HTTP/1.1 429").unwrap(), (429, ""));
+        assert_eq!(
+            parse_status_line("HTTP/1.0 503 Service Unavailable").unwrap(),
+            (503, "Service Unavailable")
+        );
+        // A 200 somewhere in the line is not a 200 status
+        for line in [
+            "HTTP/1.1 500 Error 200",
+            "200 OK",
+            "HTTP/2 200 OK",
+            "HTTP/1.1 2000 OK",
+            "HTTP/1.1 20 OK",
+            "HTTP/1.1 +20 OK",
+            "HTTP/1.1 999 Nope",
+            "garbage",
+            "",
+        ] {
+            let result = parse_status_line(line);
+            assert!(
+                result.is_err() || result.unwrap().0 != 200,
+                "accepted {:?}",
+                line
+            );
+        }
+        assert!(parse_status_line("HTTP/1.1 20 OK").is_err());
+    }
+
+    #[test]
+    fn test_parse_edge_cases() {
+        // Interim responses are skipped
+        let raw =
+            b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 201 Created\r\nContent-Length: 2\r\n\r\nok";
+        let response = HttpResponse::parse(raw).unwrap();
+        assert_eq!(response.status, 201);
+        assert_eq!(response.body, b"ok");
+
+        // Bare LF line endings and folded headers
+        let raw = b"HTTP/1.1 200 OK\nX-Long: one\n two\n\nbody";
+        let response = HttpResponse::parse(raw).unwrap();
+        assert_eq!(response.header("x-long"), Some("one two"));
+        assert_eq!(response.body, b"body");
+
+        // No body for 204 whatever follows
+        let response = HttpResponse::parse(b"HTTP/1.1 204 No Content\r\n\r\nxyz").unwrap();
+        assert!(response.body.is_empty());
+
+        for malformed in [
+            &b"HTTP/1.1 200 OK\r\n"[..],
+            b"HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\nshort",
+            b"HTTP/1.1 200 OK\r\nContent-Length: 2\r\nContent-Length: 3\r\n\r\nabc",
+            b"HTTP/1.1 200 OK\r\nContent-Length: -1\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nNo colon here\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nBad Name: x\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\n folded first\r\n\r\n",
+            b"HTTP/1.1 200 OK\r\nX: \xff\r\n\r\n",
+        ] {
+            assert!(
+                HttpResponse::parse(malformed).is_err(),
+                "accepted {:?}",
+                String::from_utf8_lossy(malformed)
+            );
+        }
+    }
+
+    // Stands in for a fuzzer: every prefix and many corruptions of valid
+    // responses must produce a result or an error, never a panic
+    #[test]
+    fn test_parse_never_panics() {
+        let samples: [&[u8]; 3] = [
+            b"HTTP/1.1 200 OK\r\nContent-Length: 12\r\n\r\n{\"status\":1}",
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n4;x\r\nabcd\r\n0\r\n\r\n",
+            b"HTTP/1.1 100 Continue\r\n\r\nHTTP/1.1 503 Busy\r\n\r\n",
+        ];
+        let mut state: u32 = 0x9E37_79B9;
+        for sample in samples {
+            for end in 0..=sample.len() {
+                let _ = HttpResponse::parse(&sample[..end]);
+            }
+            for _ in 0..2000 {
+                let mut corrupted = sample.to_vec();
+                for _ in 0..3 {
+                    // xorshift32
+                    state ^= state << 13;
+                    state ^= state >> 17;
+                    state ^= state << 5;
+                    let index = state as usize % corrupted.len();
+                    corrupted[index] = (state >> 24) as u8;
+                }
+                let _ = HttpResponse::parse(&corrupted);
+            }
+        }
+    }
+}
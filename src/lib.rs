@@ -1,27 +1,45 @@
-use serde::{Deserialize, Serialize};
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct PushoverConfig {
-    pub user: String,
-    pub token: String,
-    #[serde(default)]
-    pub default_title: Option<String>,
-}
-
-#[derive(Debug, Deserialize, Serialize, Default)]
-pub struct NotificationConfig {
-    #[serde(default)]
-    pub sound: Option<String>,
-    #[serde(default)]
-    pub device: Option<String>,
-}
-
-#[derive(Debug, Deserialize, Serialize)]
-pub struct Config {
-    pub pushover: PushoverConfig,
-    #[serde(default)]
-    pub notification: Option<NotificationConfig>,
-}
+mod attachment;
+mod cache;
+mod client;
+mod config;
+mod error;
+mod groups;
+mod http;
+mod limits;
+mod message;
+#[cfg(feature = "mock")]
+pub mod mock;
+mod proxy;
+mod receipts;
+mod response;
+mod retry;
+mod secret;
+mod sounds;
+mod timeout;
+mod users;
+
+pub use attachment::{mime_type_for_path, Attachment, MAX_ATTACHMENT_SIZE};
+pub use client::{Client, PUSHOVER_API_URL};
+pub use config::{
+    check_permissions, config_files, config_search_paths, load_config, load_config_from,
+    user_config_paths, Config, LoadedConfig, NetworkConfig, NotificationConfig, PermissionProblem,
+    ProfileConfig, PushoverConfig, DEFAULT_PROFILE, ENVIRONMENT_OVERRIDES, KEY_LEN,
+    LOCAL_CONFIG_PATH, SYSTEM_CONFIG_PATH,
+};
+pub use error::Error;
+pub use groups::{Group, GroupInfo, GroupMember, MAX_MEMO_LEN};
+pub use http::HttpResponse;
+pub use limits::{AppLimits, DEFAULT_LIMIT_WARNING};
+pub use message::{
+    truncate_message, Message, DEFAULT_TRUNCATION_MARKER, EMERGENCY_PRIORITY, MAX_DEVICE_NAME_LEN,
+    MAX_EXPIRE, MAX_MESSAGE_LEN, MAX_TITLE_LEN, MAX_URL_LEN, MAX_URL_TITLE_LEN, MIN_RETRY,
+};
+pub use receipts::{Receipt, MIN_POLL_INTERVAL};
+pub use response::{ApiError, SendResponse};
+pub use retry::{RetryPolicy, DEFAULT_RETRIES};
+pub use sounds::{closest_sound, sounds_cache_path, BUILTIN_SOUNDS, SOUNDS_CACHE_TTL};
+pub use timeout::{Timeouts, DEFAULT_CONNECT_TIMEOUT, DEFAULT_READ_TIMEOUT, DEFAULT_TIMEOUT};
+pub use users::{validation_cache_path, UserValidation, VALIDATION_CACHE_TTL};
 
 pub fn url_encode(s: &str) -> String {
     s.chars()
@@ -36,9 +54,9 @@ pub fn url_encode(s: &str) -> String {
         .collect()
 }
 
-pub fn parse_url(url: &str) -> Result<(String, u16, String), Box<dyn std::error::Error>> {
+pub fn parse_url(url: &str) -> Result<(String, u16, String), Error> {
     if !url.starts_with("https://") {
-        return Err("Only HTTPS URLs are supported".into());
+        return Err(Error::Config("Only HTTPS URLs are supported".to_string()));
     }
 
     let url_without_scheme = &url[8..]; // Remove "https://"
@@ -53,7 +71,10 @@ pub fn parse_url(url: &str) -> Result<(String, u16, String), Box<dyn std::error:
 
     let (host, port) = if host_port.contains(':') {
         let host_port_parts: Vec<&str> = host_port.splitn(2, ':').collect();
-        (host_port_parts[0].to_string(), host_port_parts[1].parse()?)
+        let port = host_port_parts[1]
+            .parse()
+            .map_err(|_| Error::Config(format!("Invalid port in URL {}", url)))?;
+        (host_port_parts[0].to_string(), port)
     } else {
         (host_port.to_string(), 443)
     };
@@ -61,31 +82,6 @@ pub fn parse_url(url: &str) -> Result<(String, u16, String), Box<dyn std::error:
     Ok((host, port, path))
 }
 
-pub fn load_config() -> Result<Config, Box<dyn std::error::Error>> {
-    use std::fs;
-
-    // Try system config first, then fallback to local config for development
-    let system_config = "/etc/pushover/config.toml";
-    let local_config = "etc/pushover/config.toml";
-
-    let (config_path, config_content) = if let Ok(content) = fs::read_to_string(system_config) {
-        (system_config, content)
-    } else if let Ok(content) = fs::read_to_string(local_config) {
-        (local_config, content)
-    } else {
-        return Err(format!(
-            "Config file not found. Tried {} and {}",
-            system_config, local_config
-        )
-        .into());
-    };
-
-    let config: Config = toml::from_str(&config_content)
-        .map_err(|e| format!("Invalid TOML in config file {}: {}", config_path, e))?;
-
-    Ok(config)
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -131,37 +127,4 @@ mod tests {
         assert!(parse_url("ftp://example.com").is_err());
         assert!(parse_url("not-a-url").is_err());
     }
-
-    #[test]
-    fn test_config_parsing() {
-        let config_content = r#"
-[pushover]
-user = "test_user_key"
-token = "test_app_token"
-default_title = "Test Title"
-
-[notification]
-sound = "pushover"
-device = "iphone"
-"#;
-
-        let config: Config = toml::from_str(config_content).unwrap();
-        assert_eq!(config.pushover.user, "test_user_key");
-        assert_eq!(config.pushover.token, "test_app_token");
-        assert_eq!(
-            config.pushover.default_title,
-            Some("Test Title".to_string())
-        );
-        assert!(config.notification.is_some());
-        let notification = config.notification.unwrap();
-        assert_eq!(notification.sound, Some("pushover".to_string()));
-        assert_eq!(notification.device, Some("iphone".to_string()));
-    }
-
-    #[test]
-    fn test_notification_config_defaults() {
-        let config = NotificationConfig::default();
-        assert!(config.sound.is_none());
-        assert!(config.device.is_none());
-    }
 }
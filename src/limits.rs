@@ -0,0 +1,68 @@
+use serde::Deserialize;
+
+use crate::http::HttpResponse;
+use crate::{Client, Error};
+
+/// Remaining messages below which the CLI warns after sending, unless
+/// `limit_warning` is configured.
+pub const DEFAULT_LIMIT_WARNING: u64 = 100;
+
+/// An application's monthly message quota.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+pub struct AppLimits {
+    /// Messages the application may send per month.
+    pub limit: u64,
+    /// Messages left until the next reset.
+    pub remaining: u64,
+    /// When the quota is reset, in seconds since the Unix epoch.
+    pub reset: u64,
+}
+
+impl AppLimits {
+    /// Reads the `X-Limit-App-Limit`, `-Remaining` and `-Reset` headers
+    /// Pushover adds to message responses, if all three are present.
+    pub(crate) fn from_headers(response: &HttpResponse) -> Option<AppLimits> {
+        let number = |name: &str| response.header(name)?.trim().parse().ok();
+        Some(AppLimits {
+            limit: number("X-Limit-App-Limit")?,
+            remaining: number("X-Limit-App-Remaining")?,
+            reset: number("X-Limit-App-Reset")?,
+        })
+    }
+}
+
+impl Client {
+    /// Fetches the application's monthly message limit and how much of it
+    /// is left, without sending a message.
+    pub fn limits(&self) -> Result<AppLimits, Error> {
+        self.get("apps/limits.json")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limits_from_headers() {
+        let raw = b"HTTP/1.1 200 OK\r\n\
+            X-Limit-App-Limit: 10000\r\n\
+            X-Limit-App-Remaining: 7496\r\n\
+            X-Limit-App-Reset: 1393653600\r\n\
+            \r\n";
+        let response = HttpResponse::parse(raw).unwrap();
+        assert_eq!(
+            AppLimits::from_headers(&response),
+            Some(AppLimits {
+                limit: 10000,
+                remaining: 7496,
+                reset: 1393653600,
+            })
+        );
+
+        let raw =
+            b"HTTP/1.1 200 OK\r\nX-Limit-App-Limit: 10000\r\nX-Limit-App-Remaining: many\r\n\r\n";
+        let response = HttpResponse::parse(raw).unwrap();
+        assert_eq!(AppLimits::from_headers(&response), None);
+    }
+}
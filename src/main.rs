@@ -1,174 +1,358 @@
 use std::env;
-use std::io::{Read, Write};
-use std::net::TcpStream;
+use std::fs;
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 use std::process;
-use std::sync::Arc;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
-use rustls::{ClientConfig, ClientConnection, StreamOwned};
-use webpki_roots::TLS_SERVER_ROOTS;
+use pushover::{
+    check_permissions, closest_sound, config_files, config_search_paths, load_config_from,
+    sounds_cache_path, truncate_message, user_config_paths, validation_cache_path, Attachment,
+    Client, Config, Error, Message, NotificationConfig, Receipt, RetryPolicy, Timeouts,
+    BUILTIN_SOUNDS, DEFAULT_LIMIT_WARNING, DEFAULT_RETRIES, DEFAULT_TRUNCATION_MARKER,
+    EMERGENCY_PRIORITY, MAX_EXPIRE, MAX_MESSAGE_LEN, MIN_POLL_INTERVAL, MIN_RETRY,
+};
 
-use pushover::{load_config, parse_url, url_encode, Config};
+/// Exit status when `--wait-ack` gives up because the alert expired unacknowledged.
+const EXIT_NOT_ACKNOWLEDGED: i32 = 2;
 
-const PUSHOVER_API_URL: &str = "https://api.pushover.net/1/messages.json";
+// Exit statuses for each kind of library error, so scripts can tell a broken
+// setup from a message Pushover rejected or throttled
+const EXIT_CONFIG: i32 = 3;
+const EXIT_VALIDATION: i32 = 4;
+const EXIT_IO: i32 = 5;
+const EXIT_TLS: i32 = 6;
+const EXIT_HTTP: i32 = 7;
+const EXIT_API: i32 = 8;
+const EXIT_RATE_LIMITED: i32 = 9;
+const EXIT_TIMEOUT: i32 = 10;
 
-fn usage() {
+fn usage() -> ! {
     let program_name = env::args().next().unwrap_or_else(|| "pushover".to_string());
     eprintln!("Usage: {} -t <title> -m <message> [OPTIONS]", program_name);
+    eprintln!(
+        "       {} receipt <receipt> [--wait-ack [--max-wait <seconds>]]",
+        program_name
+    );
+    eprintln!("       {} cancel <receipt> | --tag <tag>", program_name);
+    eprintln!(
+        "       {} validate [<user>] [--device <a,b,...>]",
+        program_name
+    );
+    eprintln!("       {} sounds", program_name);
+    eprintln!("       {} limits", program_name);
+    eprintln!(
+        "       {} group create|list|info|add|remove|enable|disable|rename [OPTIONS]",
+        program_name
+    );
+    eprintln!(
+        "       {} config init|validate|show|path|check [OPTIONS]",
+        program_name
+    );
     eprintln!("  -t <title>      Title of the notification");
-    eprintln!("  -m <message>    Message of the notification");
-    eprintln!("  -p <priority>   Priority (-2 to 2, default: 0)");
+    eprintln!("  -m <message>    Message of the notification (- reads standard input)");
+    eprintln!("  --message-file <path>  Read the message from a file");
+    eprintln!("  -p <priority>   Priority (-2 to 2, default: from config, else 0)");
+    eprintln!("  --attach <file>      Attach an image (JPEG, PNG, GIF, WebP or BMP, max 5 MB)");
+    eprintln!("  --sound <name>       Notification sound (see pushover sounds)");
+    eprintln!("  --device <a,b,...>   Deliver only to these devices (repeatable)");
+    eprintln!(
+        "  --retry <seconds>    Emergency priority: retry interval (min {})",
+        MIN_RETRY
+    );
+    eprintln!(
+        "  --expire <seconds>   Emergency priority: stop retrying after (max {})",
+        MAX_EXPIRE
+    );
+    eprintln!("  --callback <url>     Emergency priority: URL called on acknowledgement");
+    eprintln!("  --tag <tag>          Emergency priority: tag for cancel --tag (repeatable)");
+    eprintln!("  --wait-ack           Emergency priority: wait until acknowledged;");
+    eprintln!(
+        "                       exits {} if the alert expires unacknowledged",
+        EXIT_NOT_ACKNOWLEDGED
+    );
+    eprintln!(
+        "  --max-wait <seconds> With --wait-ack: stop waiting after this long; exits {}",
+        EXIT_TIMEOUT
+    );
     eprintln!("  --app-token <token>  Override app token from config");
+    eprintln!("  --timeout <seconds>  Give up on the whole request after this long,");
+    eprintln!("                       retries included (default 60)");
+    eprintln!(
+        "  --retries <count>    Retry failed connections, timeouts, 5xx and 429 (default {})",
+        DEFAULT_RETRIES
+    );
+    eprintln!("  --config <path>      Read configuration from this file only");
+    eprintln!("  --profile <name>     Use the [profiles.<name>] overrides from the config");
+    eprintln!("  -v, --verbose   Print the Pushover request id after sending");
     eprintln!("  -h, --help      Show this help message");
     eprintln!();
+    eprintln!("Without -m, the message is read from standard input when it is not a terminal.");
+    eprintln!(
+        "Messages longer than {} characters are truncated.",
+        MAX_MESSAGE_LEN
+    );
+    eprintln!();
+    eprintln!("Commands:");
+    eprintln!("  receipt <receipt>    Show the status of an emergency notification");
+    eprintln!("  cancel <receipt>     Stop retrying an emergency notification");
+    eprintln!("  cancel --tag <tag>   Stop retrying all emergency notifications with a tag");
+    eprintln!("  validate [<user>]    Check a user or group key (default: from config) and");
+    eprintln!("                       --device with Pushover; lists the user's devices");
+    eprintln!("  sounds               List the sounds available, including custom ones");
+    eprintln!("  limits               Show the application's monthly message limit and");
+    eprintln!("                       what is left of it");
+    eprintln!("  group create <name>  Create a delivery group and print its key");
+    eprintln!("  group list           List the delivery groups");
+    eprintln!("  group info <group>   Show a group's name and members");
+    eprintln!("  group add <group> <user>  Add a user (--device, --memo)");
+    eprintln!("  group remove|enable|disable <group> <user>");
+    eprintln!("                       Remove, resume or pause a user (--device)");
+    eprintln!("  group rename <group> <name>  Rename a group");
+    eprintln!("  config init          Write a config file (--user, --token, --title, --sound,");
+    eprintln!("                       --device; asks for the keys if not given; --force)");
+    eprintln!("  config validate      Check the config for placeholders and malformed keys;");
+    eprintln!("                       --online also asks Pushover about the user and device");
+    eprintln!("  config show          Print the effective configuration with keys redacted");
+    eprintln!("  config path          List the config files looked for and which are read");
+    eprintln!("  config check         Report config files other users can read or change");
+    eprintln!();
     eprintln!("Configuration:");
-    eprintln!("  Reads configuration from /etc/pushover/config.toml");
+    eprintln!("  Reads --config or $PUSHOVER_CONFIG if given. Otherwise the first of");
+    eprintln!("  $XDG_CONFIG_HOME/pushover/config.toml and ~/.config/pushover/config.toml");
+    eprintln!("  overrides /etc/pushover/config.toml one setting at a time.");
     eprintln!("  Falls back to etc/pushover/config.toml for development");
+    eprintln!("  PUSHOVER_USER, PUSHOVER_TOKEN, PUSHOVER_DEFAULT_TITLE, PUSHOVER_SOUND,");
+    eprintln!("  PUSHOVER_DEVICE and PUSHOVER_PRIORITY override the files; with the user");
+    eprintln!("  key or token set in the environment no file is needed");
+    eprintln!("  Connects through the [network] proxy, else HTTPS_PROXY, unless NO_PROXY");
+    eprintln!("  lists the API host");
+    eprintln!(
+        "  Warns when fewer than {} messages are left this month (limit_warning",
+        DEFAULT_LIMIT_WARNING
+    );
+    eprintln!("  in [pushover], 0 to disable)");
+    eprintln!("  Files with unsafe permissions are reported, or refused with");
+    eprintln!("  strict_permissions = true in [pushover]");
+    eprintln!();
+    eprintln!("Exit status:");
+    eprintln!(
+        "  0 success, 1 usage error, {} not acknowledged, {} configuration error,",
+        EXIT_NOT_ACKNOWLEDGED, EXIT_CONFIG
+    );
+    eprintln!(
+        "  {} invalid message, {} network or file error, {} TLS error,",
+        EXIT_VALIDATION, EXIT_IO, EXIT_TLS
+    );
+    eprintln!(
+        "  {} unexpected HTTP response, {} rejected by Pushover, {} rate limited,",
+        EXIT_HTTP, EXIT_API, EXIT_RATE_LIMITED
+    );
+    eprintln!("  {} timed out", EXIT_TIMEOUT);
     process::exit(1);
 }
 
-fn send_notification_rustls(
-    config: &Config,
-    title: &str,
-    message: &str,
-    priority: i8,
-    app_token_override: Option<&str>,
-) -> Result<(), Box<dyn std::error::Error>> {
-    let (host, port, path) = parse_url(PUSHOVER_API_URL)?;
-
-    // Build form data
-    let token = app_token_override.unwrap_or(&config.pushover.token);
-    let mut form_parts = vec![
-        format!("token={}", url_encode(token)),
-        format!("user={}", url_encode(&config.pushover.user)),
-        format!("title={}", url_encode(title)),
-        format!("message={}", url_encode(message)),
-    ];
-
-    // Add priority if not default
-    if priority != 0 {
-        form_parts.push(format!("priority={}", priority));
-    }
-
-    // Add optional notification settings
-    if let Some(notification) = &config.notification {
-        if let Some(sound) = &notification.sound {
-            form_parts.push(format!("sound={}", url_encode(sound)));
-        }
-        if let Some(device) = &notification.device {
-            form_parts.push(format!("device={}", url_encode(device)));
-        }
-    }
-
-    let form_data = form_parts.join("&");
-
-    // Create TLS config
-    let mut root_store = rustls::RootCertStore::empty();
-    root_store.add_trust_anchors(TLS_SERVER_ROOTS.iter().map(|ta| {
-        rustls::OwnedTrustAnchor::from_subject_spki_name_constraints(
-            ta.subject,
-            ta.spki,
-            ta.name_constraints,
-        )
-    }));
-
-    let config = ClientConfig::builder()
-        .with_safe_defaults()
-        .with_root_certificates(root_store)
-        .with_no_client_auth();
-
-    // Connect to server
-    let server_name = rustls::ServerName::try_from(host.as_str())?;
-    let conn = ClientConnection::new(Arc::new(config), server_name)?;
-    let sock = TcpStream::connect(format!("{}:{}", host, port))?;
-    let mut tls = StreamOwned::new(conn, sock);
-
-    // Build HTTP request
-    let request = format!(
-        "POST {} HTTP/1.1\r\n\
-         Host: {}\r\n\
-         Content-Type: application/x-www-form-urlencoded\r\n\
-         Content-Length: {}\r\n\
-         Connection: close\r\n\
-         User-Agent: pushover-rust/1.0\r\n\
-         \r\n\
-         {}",
-        path,
-        host,
-        form_data.len(),
-        form_data
-    );
+fn exit_code(error: &Error) -> i32 {
+    match error {
+        Error::Config(_) => EXIT_CONFIG,
+        Error::Validation(_) => EXIT_VALIDATION,
+        Error::Io(_) => EXIT_IO,
+        Error::Tls(_) => EXIT_TLS,
+        Error::Http { .. } => EXIT_HTTP,
+        Error::Api(_) => EXIT_API,
+        Error::RateLimited(_) => EXIT_RATE_LIMITED,
+        Error::Timeout(_) => EXIT_TIMEOUT,
+    }
+}
+
+/// Prints `error` after `context` and exits with the status for its kind.
+fn fail(context: &str, error: Error) -> ! {
+    eprintln!("{}: {}", context, error);
+    process::exit(exit_code(&error));
+}
+
+/// Returns the value following the option at `args[i]`, or exits with usage.
+fn option_value(args: &[String], i: usize) -> &str {
+    if i + 1 >= args.len() {
+        eprintln!("Option {} requires an argument.", args[i]);
+        usage();
+    }
+    &args[i + 1]
+}
 
-    // Send request
-    tls.write_all(request.as_bytes())?;
+/// The `--config` and `--profile` options, shared by every command.
+#[derive(Default)]
+struct ConfigOptions {
+    path: Option<String>,
+    profile: Option<String>,
+}
 
-    // Read response
-    let mut response = Vec::new();
-    tls.read_to_end(&mut response)?;
+impl ConfigOptions {
+    /// Records the value of the `--config` or `--profile` option at `args[i]`.
+    fn set(&mut self, args: &[String], i: usize) {
+        let value = Some(option_value(args, i).to_string());
+        match args[i].as_str() {
+            "--config" => self.path = value,
+            _ => self.profile = value,
+        }
+    }
 
-    // Parse response to check for errors
-    let response_str = String::from_utf8_lossy(&response);
-    if let Some(status_line) = response_str.lines().next() {
-        if !status_line.contains("200") {
-            return Err(format!("HTTP request failed: {}", status_line).into());
+    /// Loads the configuration from `--config` or the standard locations and
+    /// applies the selected profile, reporting the files used in verbose mode.
+    fn load_or_exit(&self, verbose: bool) -> Config {
+        let loaded = match load_config_from(self.path.as_deref().map(Path::new)) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                eprintln!("Error loading configuration: {}", e);
+                eprintln!(
+                    "Please ensure ~/.config/pushover/config.toml or /etc/pushover/config.toml exists and is properly configured."
+                );
+                process::exit(exit_code(&e));
+            }
+        };
+        if verbose {
+            for file in &loaded.files {
+                eprintln!("Using configuration file {}", file.display());
+            }
+            for name in &loaded.environment {
+                eprintln!("Using environment variable {}", name);
+            }
+        }
+        for problem in &loaded.permission_problems {
+            eprintln!("Warning: {}; fix with: {}", problem, problem.fix);
+        }
+
+        match &self.profile {
+            Some(profile) => match loaded.config.with_profile(profile) {
+                Ok(config) => {
+                    if verbose {
+                        eprintln!("Using profile {}", profile);
+                    }
+                    config
+                }
+                Err(e) => fail("Error loading configuration", e),
+            },
+            None => loaded.config,
         }
     }
+}
 
-    Ok(())
+/// The `--app-token`, `--timeout` and `--retries` options of the commands
+/// that call the API.
+#[derive(Default)]
+struct ClientOptions {
+    app_token: Option<String>,
+    timeout: Option<u64>,
+    retries: Option<u32>,
 }
 
-fn main() {
-    // Load configuration
-    let config = match load_config() {
-        Ok(config) => config,
-        Err(e) => {
-            eprintln!("Error loading configuration: {}", e);
-            eprintln!("Please ensure /etc/pushover/config.toml exists and is properly configured.");
-            process::exit(1);
+impl ClientOptions {
+    /// Records the value of the option at `args[i]`, or exits with usage if
+    /// it is not valid.
+    fn set(&mut self, args: &[String], i: usize) {
+        let value = option_value(args, i);
+        match args[i].as_str() {
+            "--app-token" => self.app_token = Some(value.to_string()),
+            "--timeout" => match value.parse::<u64>() {
+                Ok(seconds) if seconds > 0 => self.timeout = Some(seconds),
+                _ => {
+                    eprintln!("Timeout must be a positive number of seconds.");
+                    usage();
+                }
+            },
+            _ => match value.parse::<u32>() {
+                Ok(retries) => self.retries = Some(retries),
+                Err(_) => {
+                    eprintln!("Retries must be a number.");
+                    usage();
+                }
+            },
         }
+    }
+}
+
+fn build_client(config: &Config, options: &ClientOptions) -> Client {
+    let mut client = match Client::from_config(config) {
+        Ok(client) => client,
+        Err(e) => fail("Error loading configuration", e),
     };
+    if let Some(token) = &options.app_token {
+        client = client.with_token(token);
+    }
+    if let Some(seconds) = options.timeout {
+        let timeouts = Timeouts {
+            overall: Duration::from_secs(seconds),
+            ..client.timeouts()
+        };
+        client = client.with_timeouts(timeouts);
+    }
+    if let Some(retries) = options.retries {
+        let retry_policy = RetryPolicy {
+            max_retries: retries,
+            ..client.retry_policy()
+        };
+        client = client.with_retry_policy(retry_policy);
+    }
+    client
+}
 
-    // Determine default title
-    let default_title = config.pushover.default_title.clone().unwrap_or_else(|| {
-        format!(
-            "{} @",
-            env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string())
-        )
-    });
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
 
-    let mut title = default_title;
-    let mut message = String::new();
-    let mut priority: i8 = 0;
-    let mut app_token_override: Option<String> = None;
+    match args.first().map(String::as_str) {
+        Some("receipt") => receipt_command(&args[1..]),
+        Some("cancel") => cancel_command(&args[1..]),
+        Some("config") => config_command(&args[1..]),
+        Some("validate") => validate_command(&args[1..]),
+        Some("sounds") => sounds_command(&args[1..]),
+        Some("limits") => limits_command(&args[1..]),
+        Some("group") => group_command(&args[1..]),
+        _ => send_command(&args),
+    }
+}
+
+fn send_command(args: &[String]) {
+    let mut title: Option<String> = None;
+    let mut config_options = ConfigOptions::default();
+    let mut message: Option<String> = None;
+    let mut message_file: Option<String> = None;
+    let mut priority: Option<i8> = None;
+    let mut client_options = ClientOptions::default();
+    let mut retry: Option<u32> = None;
+    let mut expire: Option<u32> = None;
+    let mut callback: Option<String> = None;
+    let mut tags: Vec<String> = Vec::new();
+    let mut attach: Option<String> = None;
+    let mut sound: Option<String> = None;
+    let mut devices: Vec<String> = Vec::new();
+    let mut wait_ack = false;
+    let mut max_wait: Option<Duration> = None;
+    let mut verbose = false;
 
     // Parse command line arguments
-    let args: Vec<String> = env::args().collect();
-    let mut i = 1;
+    let mut i = 0;
 
     while i < args.len() {
         match args[i].as_str() {
             "-t" => {
-                if i + 1 >= args.len() {
-                    eprintln!("Option -t requires an argument.");
-                    usage();
-                }
-                title = args[i + 1].clone();
+                title = Some(option_value(args, i).to_string());
+                i += 2;
+            }
+            "--config" | "--profile" => {
+                config_options.set(args, i);
                 i += 2;
             }
             "-m" => {
-                if i + 1 >= args.len() {
-                    eprintln!("Option -m requires an argument.");
-                    usage();
-                }
-                message = args[i + 1].clone();
+                message = Some(option_value(args, i).to_string());
+                i += 2;
+            }
+            "--message-file" => {
+                message_file = Some(option_value(args, i).to_string());
                 i += 2;
             }
             "-p" => {
-                if i + 1 >= args.len() {
-                    eprintln!("Option -p requires an argument.");
-                    usage();
-                }
-                match args[i + 1].parse::<i8>() {
-                    Ok(p) if (-2..=2).contains(&p) => priority = p,
+                match option_value(args, i).parse::<i8>() {
+                    Ok(p) if (-2..=2).contains(&p) => priority = Some(p),
                     Ok(_) => {
                         eprintln!("Priority must be between -2 and 2.");
                         usage();
@@ -180,14 +364,70 @@ fn main() {
                 };
                 i += 2;
             }
-            "--app-token" => {
-                if i + 1 >= args.len() {
-                    eprintln!("Option --app-token requires an argument.");
-                    usage();
-                }
-                app_token_override = Some(args[i + 1].clone());
+            "--app-token" | "--timeout" | "--retries" => {
+                client_options.set(args, i);
+                i += 2;
+            }
+            "--retry" => {
+                match option_value(args, i).parse::<u32>() {
+                    Ok(r) if r >= MIN_RETRY => retry = Some(r),
+                    Ok(_) => {
+                        eprintln!("Retry must be at least {} seconds.", MIN_RETRY);
+                        usage();
+                    }
+                    Err(_) => {
+                        eprintln!("Retry must be a number of seconds.");
+                        usage();
+                    }
+                };
                 i += 2;
             }
+            "--expire" => {
+                match option_value(args, i).parse::<u32>() {
+                    Ok(e) if e <= MAX_EXPIRE => expire = Some(e),
+                    Ok(_) => {
+                        eprintln!("Expire must be at most {} seconds.", MAX_EXPIRE);
+                        usage();
+                    }
+                    Err(_) => {
+                        eprintln!("Expire must be a number of seconds.");
+                        usage();
+                    }
+                };
+                i += 2;
+            }
+            "--callback" => {
+                callback = Some(option_value(args, i).to_string());
+                i += 2;
+            }
+            "--tag" => {
+                tags.push(option_value(args, i).to_string());
+                i += 2;
+            }
+            "--attach" => {
+                attach = Some(option_value(args, i).to_string());
+                i += 2;
+            }
+            "--sound" => {
+                sound = Some(option_value(args, i).to_string());
+                i += 2;
+            }
+            "--device" => {
+                devices.push(option_value(args, i).to_string());
+                i += 2;
+            }
+            "--wait-ack" => {
+                wait_ack = true;
+                i += 1;
+            }
+            "--max-wait" => {
+                max_wait = Some(max_wait_value(args, i));
+                i += 2;
+            }
+            "-v" | "--verbose" => {
+                verbose = true;
+                i += 1;
+            }
             "-h" | "--help" => {
                 usage();
             }
@@ -202,26 +442,951 @@ fn main() {
         }
     }
 
+    if message.is_some() && message_file.is_some() {
+        eprintln!("Use either -m or --message-file, not both.");
+        usage();
+    }
+
+    let config = config_options.load_or_exit(verbose);
+
+    // Fall back to the configured default title, then to the host name
+    let title = title
+        .or_else(|| config.pushover.default_title.clone())
+        .unwrap_or_else(|| {
+            format!(
+                "{} @",
+                env::var("HOSTNAME").unwrap_or_else(|_| "localhost".to_string())
+            )
+        });
+
+    let message = match read_message(message, message_file) {
+        Ok(message) => message,
+        Err(e) => fail("Error reading message", e),
+    };
+
     // Check if message is provided
-    if message.is_empty() {
+    if message.trim().is_empty() {
         eprintln!("Message is required.");
         usage();
     }
 
+    let marker = config
+        .notification
+        .as_ref()
+        .and_then(|n| n.truncation_marker.as_deref())
+        .unwrap_or(DEFAULT_TRUNCATION_MARKER);
+    let truncated = truncate_message(&message, MAX_MESSAGE_LEN, marker);
+    if verbose && truncated != message {
+        eprintln!(
+            "Message truncated to the {} character limit",
+            MAX_MESSAGE_LEN
+        );
+    }
+    // Without -p, use the priority from the configuration or profile
+    let priority = priority
+        .or_else(|| config.notification.as_ref().and_then(|n| n.priority))
+        .unwrap_or(0);
+    if wait_ack && priority != EMERGENCY_PRIORITY {
+        eprintln!("--wait-ack requires emergency priority (-p 2).");
+        usage();
+    }
+    if max_wait.is_some() && !wait_ack {
+        eprintln!("--max-wait requires --wait-ack.");
+        usage();
+    }
+
+    let client = build_client(&config, &client_options);
+    // Devices from the command line replace the configured ones
+    let device = if devices.is_empty() {
+        config.notification.as_ref().and_then(|n| n.device.clone())
+    } else {
+        Some(devices.join(","))
+    };
+    if config.pushover.validate_on_load {
+        check_user_and_device(&client, device.as_deref(), verbose);
+    }
+    let mut message = Message::new(truncated).title(title).priority(priority);
+    if let Some(retry) = retry {
+        message = message.retry(retry);
+    }
+    if let Some(expire) = expire {
+        message = message.expire(expire);
+    }
+    if let Some(callback) = callback {
+        message = message.callback(callback);
+    }
+    // Whether from --sound or the configuration, check the sound that will be used
+    if let Some(sound) = sound
+        .as_deref()
+        .or_else(|| config.notification.as_ref()?.sound.as_deref())
+    {
+        check_sound(&client, sound, verbose);
+    }
+    if let Some(sound) = sound {
+        message = message.sound(sound);
+    }
+    for device in devices {
+        message = message.device(device);
+    }
+    for tag in tags {
+        message = message.tag(tag);
+    }
+    if let Some(path) = attach {
+        match Attachment::from_path(&path) {
+            Ok(attachment) => message = message.attachment(attachment),
+            Err(e) => fail("Error reading attachment", e),
+        }
+    }
+
     // Send the notification
-    match send_notification_rustls(
-        &config,
-        &title,
-        &message,
-        priority,
-        app_token_override.as_deref(),
-    ) {
-        Ok(()) => {
-            // Success - silent like the original script
+    let response = match client.send(&message) {
+        Ok(response) => response,
+        Err(e) => fail("Error sending notification", e),
+    };
+
+    // Success - silent like the original script unless asked otherwise,
+    // or the application is about to run out of messages
+    let threshold = config
+        .pushover
+        .limit_warning
+        .unwrap_or(DEFAULT_LIMIT_WARNING);
+    if let Some(limits) = response.limits.filter(|l| l.remaining < threshold) {
+        eprintln!(
+            "Warning: {} of the application's {} monthly messages left; the limit resets {}",
+            limits.remaining,
+            limits.limit,
+            until_reset(limits.reset)
+        );
+    }
+    if verbose {
+        eprintln!("Notification sent (request {})", response.request);
+        if let Some(receipt) = &response.receipt {
+            eprintln!("Receipt: {}", receipt);
+        }
+    }
+
+    if wait_ack {
+        match response.receipt {
+            Some(receipt) => wait_for_ack(&client, &receipt, max_wait, verbose),
+            None => {
+                eprintln!("Pushover did not return a receipt to wait on.");
+                process::exit(1);
+            }
+        }
+    }
+}
+
+/// Resolves the message body from `-m`, `--message-file` or standard input.
+///
+/// `-m -` reads standard input explicitly; without `-m` or `--message-file`,
+/// standard input is read when it is not a terminal. A trailing newline from
+/// piped or file input is dropped. Giving both `-m` and `--message-file` is
+/// a usage error caught while parsing; `-m` wins here.
+fn read_message(message: Option<String>, message_file: Option<String>) -> Result<String, Error> {
+    let body = match (message, message_file) {
+        (Some(message), _) if message == "-" => read_stdin()?,
+        (Some(message), _) => return Ok(message),
+        (None, Some(path)) => fs::File::open(&path).and_then(read_input).map_err(|e| {
+            Error::Io(io::Error::new(
+                e.kind(),
+                format!("cannot read {}: {}", path, e),
+            ))
+        })?,
+        (None, None) if !io::stdin().is_terminal() => read_stdin()?,
+        (None, None) => String::new(),
+    };
+    Ok(body.trim_end_matches(['\n', '\r']).to_string())
+}
+
+fn read_stdin() -> Result<String, Error> {
+    read_input(io::stdin()).map_err(|e| {
+        Error::Io(io::Error::new(
+            e.kind(),
+            format!("cannot read standard input: {}", e),
+        ))
+    })
+}
+
+/// Bytes of input read for the message body: enough for [`MAX_MESSAGE_LEN`]
+/// characters of up to 4 bytes, and one more so that longer input is still
+/// truncated with the marker.
+const MAX_MESSAGE_INPUT: u64 = MAX_MESSAGE_LEN as u64 * 4 + 1;
+
+/// Reads up to [`MAX_MESSAGE_INPUT`] bytes, replacing invalid UTF-8 rather
+/// than losing the message over it.
+fn read_input(input: impl Read) -> io::Result<String> {
+    let mut bytes = Vec::new();
+    input.take(MAX_MESSAGE_INPUT).read_to_end(&mut bytes)?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+fn receipt_command(args: &[String]) {
+    let mut receipt: Option<String> = None;
+    let mut config_options = ConfigOptions::default();
+    let mut client_options = ClientOptions::default();
+    let mut wait_ack = false;
+    let mut max_wait: Option<Duration> = None;
+    let mut verbose = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--app-token" | "--timeout" | "--retries" => {
+                client_options.set(args, i);
+                i += 2;
+            }
+            "--config" | "--profile" => {
+                config_options.set(args, i);
+                i += 2;
+            }
+            "--wait-ack" => {
+                wait_ack = true;
+                i += 1;
+            }
+            "--max-wait" => {
+                max_wait = Some(max_wait_value(args, i));
+                i += 2;
+            }
+            "-v" | "--verbose" => {
+                verbose = true;
+                i += 1;
+            }
+            "-h" | "--help" => {
+                usage();
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Invalid option {}", arg);
+                usage();
+            }
+            arg if receipt.is_none() => {
+                receipt = Some(arg.to_string());
+                i += 1;
+            }
+            _ => {
+                eprintln!("Unexpected argument: {}", args[i]);
+                usage();
+            }
+        }
+    }
+
+    let Some(receipt) = receipt else {
+        eprintln!("Receipt is required.");
+        usage();
+    };
+    if max_wait.is_some() && !wait_ack {
+        eprintln!("--max-wait requires --wait-ack.");
+        usage();
+    }
+
+    let config = config_options.load_or_exit(verbose);
+    let client = build_client(&config, &client_options);
+
+    if wait_ack {
+        wait_for_ack(&client, &receipt, max_wait, verbose);
+        return;
+    }
+
+    match client.receipt(&receipt) {
+        Ok(status) => print_receipt(&status),
+        Err(e) => fail("Error fetching receipt", e),
+    }
+}
+
+fn cancel_command(args: &[String]) {
+    let mut receipt: Option<String> = None;
+    let mut tag: Option<String> = None;
+    let mut config_options = ConfigOptions::default();
+    let mut client_options = ClientOptions::default();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--tag" => {
+                tag = Some(option_value(args, i).to_string());
+                i += 2;
+            }
+            "--app-token" | "--timeout" | "--retries" => {
+                client_options.set(args, i);
+                i += 2;
+            }
+            "--config" | "--profile" => {
+                config_options.set(args, i);
+                i += 2;
+            }
+            "-h" | "--help" => {
+                usage();
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Invalid option {}", arg);
+                usage();
+            }
+            arg if receipt.is_none() => {
+                receipt = Some(arg.to_string());
+                i += 1;
+            }
+            _ => {
+                eprintln!("Unexpected argument: {}", args[i]);
+                usage();
+            }
+        }
+    }
+
+    let result = match (receipt, tag) {
+        (Some(receipt), None) => {
+            let client = build_client(&config_options.load_or_exit(false), &client_options);
+            client.cancel_receipt(&receipt)
+        }
+        (None, Some(tag)) => {
+            let client = build_client(&config_options.load_or_exit(false), &client_options);
+            client.cancel_by_tag(&tag).map(|canceled| {
+                println!("Cancelled {} notification(s) tagged {}", canceled, tag);
+            })
+        }
+        _ => {
+            eprintln!("Give either a receipt or --tag <tag>.");
+            usage();
         }
+    };
+
+    if let Err(e) = result {
+        fail("Error cancelling notification", e);
+    }
+}
+
+/// Exits with a suggestion if `sound` is not one of the application's
+/// sounds. The list is cached for a day; if it cannot be fetched, a sound
+/// that is not one of the [`BUILTIN_SOUNDS`] only gets a warning, as it may
+/// be a custom sound.
+fn check_sound(client: &Client, sound: &str, verbose: bool) {
+    let sounds = match sounds_cache_path() {
+        Some(cache_file) => client.sounds_cached(&cache_file),
+        None => client.sounds(),
+    };
+    // Only a list that really came from Pushover can show a sound is wrong;
+    // failing to fetch it must not stop the notification
+    let names: Vec<String> = match sounds {
+        Ok(sounds) => sounds.into_keys().collect(),
         Err(e) => {
-            eprintln!("Error sending notification: {}", e);
-            process::exit(1);
+            if !BUILTIN_SOUNDS.iter().any(|(name, _)| *name == sound) {
+                let builtin = BUILTIN_SOUNDS.iter().map(|(name, _)| *name);
+                eprintln!(
+                    "Warning: '{}' is not a built-in sound{}",
+                    sound,
+                    sound_suggestion(sound, builtin)
+                );
+            }
+            if verbose {
+                eprintln!("Cannot fetch the sound list to check custom sounds: {}", e);
+            }
+            return;
+        }
+    };
+    if names.iter().any(|name| name == sound) {
+        return;
+    }
+
+    fail(
+        "Invalid sound",
+        Error::Validation(format!(
+            "'{}' is not an available sound{}",
+            sound,
+            sound_suggestion(sound, names.iter().map(String::as_str))
+        )),
+    );
+}
+
+/// `; did you mean '<closest>'?`, or a pointer to `pushover sounds` when no
+/// name in `names` is close to `sound`.
+fn sound_suggestion<'a>(sound: &str, names: impl IntoIterator<Item = &'a str>) -> String {
+    match closest_sound(sound, names) {
+        Some(closest) => format!("; did you mean '{}'?", closest),
+        None => " (see pushover sounds)".to_string(),
+    }
+}
+
+fn sounds_command(args: &[String]) {
+    let mut config_options = ConfigOptions::default();
+    let mut client_options = ClientOptions::default();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--app-token" | "--timeout" | "--retries" => {
+                client_options.set(args, i);
+                i += 2;
+            }
+            "--config" | "--profile" => {
+                config_options.set(args, i);
+                i += 2;
+            }
+            "-h" | "--help" => {
+                usage();
+            }
+            arg => {
+                eprintln!("Invalid option {}", arg);
+                usage();
+            }
+        }
+    }
+
+    let client = build_client(&config_options.load_or_exit(false), &client_options);
+    let sounds = match client.sounds() {
+        Ok(sounds) => sounds,
+        Err(e) => fail("Error fetching sounds", e),
+    };
+    let width = sounds.keys().map(String::len).max().unwrap_or(0);
+    for (name, description) in &sounds {
+        println!("{:<width$}  {}", name, description, width = width);
+    }
+}
+
+fn limits_command(args: &[String]) {
+    let mut config_options = ConfigOptions::default();
+    let mut client_options = ClientOptions::default();
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--app-token" | "--timeout" | "--retries" => {
+                client_options.set(args, i);
+                i += 2;
+            }
+            "--config" | "--profile" => {
+                config_options.set(args, i);
+                i += 2;
+            }
+            "-h" | "--help" => {
+                usage();
+            }
+            arg => {
+                eprintln!("Invalid option {}", arg);
+                usage();
+            }
+        }
+    }
+
+    let client = build_client(&config_options.load_or_exit(false), &client_options);
+    let limits = match client.limits() {
+        Ok(limits) => limits,
+        Err(e) => fail("Error fetching limits", e),
+    };
+    println!("limit: {}", limits.limit);
+    println!("remaining: {}", limits.remaining);
+    println!("reset: {} ({})", limits.reset, until_reset(limits.reset));
+}
+
+/// Describes how far off the Unix time `reset` is, e.g. `in 12 days`.
+fn until_reset(reset: u64) -> String {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    // Rounded to the nearest unit, so 29 days and 23 hours reads as 30 days
+    let rounded = |left: u64, seconds: u64, unit: &str| {
+        let count = (left + seconds / 2) / seconds;
+        format!("in {} {}{}", count, unit, if count == 1 { "" } else { "s" })
+    };
+    match reset.saturating_sub(now) {
+        0 => "now".to_string(),
+        left if left >= 2 * 24 * 60 * 60 => rounded(left, 24 * 60 * 60, "day"),
+        left if left >= 2 * 60 * 60 => rounded(left, 60 * 60, "hour"),
+        left => rounded(left.max(30), 60, "minute"),
+    }
+}
+
+/// Checks the user key and the devices to deliver to before sending, for
+/// `validate_on_load`, reusing a recent successful check where possible.
+fn check_user_and_device(client: &Client, device: Option<&str>, verbose: bool) {
+    let result = match validation_cache_path() {
+        Some(cache_file) => client.validate_user_cached(client.user(), device, &cache_file),
+        None => client.validate_user(client.user(), device),
+    };
+    match result {
+        Ok(_) if verbose => eprintln!("User key and device validated"),
+        Ok(_) => {}
+        Err(e) => fail("Error validating user key", e),
+    }
+}
+
+fn validate_command(args: &[String]) {
+    let mut user: Option<String> = None;
+    let mut devices: Vec<String> = Vec::new();
+    let mut config_options = ConfigOptions::default();
+    let mut client_options = ClientOptions::default();
+    let mut verbose = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--device" => {
+                devices.push(option_value(args, i).to_string());
+                i += 2;
+            }
+            "--app-token" | "--timeout" | "--retries" => {
+                client_options.set(args, i);
+                i += 2;
+            }
+            "--config" | "--profile" => {
+                config_options.set(args, i);
+                i += 2;
+            }
+            "-v" | "--verbose" => {
+                verbose = true;
+                i += 1;
+            }
+            "-h" | "--help" => {
+                usage();
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Invalid option {}", arg);
+                usage();
+            }
+            arg if user.is_none() => {
+                user = Some(arg.to_string());
+                i += 1;
+            }
+            _ => {
+                eprintln!("Unexpected argument: {}", args[i]);
+                usage();
+            }
+        }
+    }
+
+    let config = config_options.load_or_exit(verbose);
+    let client = build_client(&config, &client_options);
+    // The configured device only applies to the configured user
+    let device = match (&user, devices.is_empty()) {
+        (_, false) => Some(devices.join(",")),
+        (Some(_), true) => None,
+        (None, true) => config.notification.and_then(|n| n.device),
+    };
+    let user = user.unwrap_or_else(|| client.user().to_string());
+
+    let validation = match client.validate_user(&user, device.as_deref()) {
+        Ok(validation) => validation,
+        Err(e) => fail("Error validating user key", e),
+    };
+    let kind = if validation.group { "Group" } else { "User" };
+    println!("{} key {} is valid", kind, user);
+    if let Some(device) = device {
+        for device in device.split(',') {
+            println!("Device {} is valid", device.trim());
+        }
+    }
+    println!("Devices: {}", validation.devices.join(", "));
+    println!("Licenses: {}", validation.licenses.join(", "));
+}
+
+fn group_command(args: &[String]) {
+    let Some(command) = args.first().map(String::as_str) else {
+        eprintln!("A group command is required.");
+        usage();
+    };
+    // The positional arguments of each command, and its options besides the
+    // ones every command takes
+    let (arguments, allowed): (&[&str], &[&str]) = match command {
+        "create" => (&["<name>"], &[]),
+        "list" => (&[], &[]),
+        "info" => (&["<group>"], &[]),
+        "add" => (&["<group>", "<user>"], &["--device", "--memo"]),
+        "remove" | "enable" | "disable" => (&["<group>", "<user>"], &["--device"]),
+        "rename" => (&["<group>", "<name>"], &[]),
+        other => {
+            eprintln!("Unknown group command: {}", other);
+            usage();
+        }
+    };
+
+    let args = &args[1..];
+    let mut positional: Vec<String> = Vec::new();
+    let mut device: Option<String> = None;
+    let mut memo: Option<String> = None;
+    let mut config_options = ConfigOptions::default();
+    let mut client_options = ClientOptions::default();
+    let mut verbose = false;
+
+    let mut i = 0;
+    while i < args.len() {
+        match args[i].as_str() {
+            "--app-token" | "--timeout" | "--retries" => {
+                client_options.set(args, i);
+                i += 2;
+            }
+            "--config" | "--profile" => {
+                config_options.set(args, i);
+                i += 2;
+            }
+            "-v" | "--verbose" => {
+                verbose = true;
+                i += 1;
+            }
+            "-h" | "--help" => {
+                usage();
+            }
+            arg if allowed.contains(&arg) => {
+                let value = Some(option_value(args, i).to_string());
+                match arg {
+                    "--device" => device = value,
+                    _ => memo = value,
+                }
+                i += 2;
+            }
+            arg if arg.starts_with('-') => {
+                eprintln!("Invalid option {} for group {}", arg, command);
+                usage();
+            }
+            arg => {
+                positional.push(arg.to_string());
+                i += 1;
+            }
+        }
+    }
+    if positional.len() != arguments.len() {
+        eprintln!("Usage: pushover group {} {}", command, arguments.join(" "));
+        usage();
+    }
+
+    let client = build_client(&config_options.load_or_exit(verbose), &client_options);
+    let (device, memo) = (device.as_deref(), memo.as_deref());
+    let result = match (command, positional.as_slice()) {
+        ("create", [name]) => client.create_group(name).map(|key| println!("{}", key)),
+        ("list", []) => client.groups().map(|groups| {
+            for group in groups {
+                println!("{}  {}", group.group, group.name);
+            }
+        }),
+        ("info", [group]) => client.group(group).map(|info| {
+            println!("Name: {}", info.name);
+            for member in info.users {
+                println!(
+                    "{}  {}  {}  {}",
+                    member.user,
+                    member.device.as_deref().unwrap_or("(all devices)"),
+                    if member.disabled {
+                        "disabled"
+                    } else {
+                        "enabled"
+                    },
+                    member.memo
+                );
+            }
+        }),
+        ("add", [group, user]) => client
+            .add_group_user(group, user, device, memo)
+            .map(|()| println!("Added {} to group {}", user, group)),
+        ("remove", [group, user]) => client
+            .remove_group_user(group, user, device)
+            .map(|()| println!("Removed {} from group {}", user, group)),
+        ("enable", [group, user]) => client
+            .enable_group_user(group, user, device)
+            .map(|()| println!("Enabled {} in group {}", user, group)),
+        ("disable", [group, user]) => client
+            .disable_group_user(group, user, device)
+            .map(|()| println!("Disabled {} in group {}", user, group)),
+        (_, [group, name]) => client
+            .rename_group(group, name)
+            .map(|()| println!("Renamed group {} to {}", group, name)),
+        _ => unreachable!("argument count checked above"),
+    };
+
+    if let Err(e) = result {
+        fail(&format!("Error running group {}", command), e);
+    }
+}
+
+/// Options of the `config` subcommands; each accepts only some of them.
+#[derive(Default)]
+struct ConfigCommandOptions {
+    config: ConfigOptions,
+    online: bool,
+    force: bool,
+    user: Option<String>,
+    token: Option<String>,
+    title: Option<String>,
+    sound: Option<String>,
+    device: Option<String>,
+}
+
+fn config_command(args: &[String]) {
+    let Some(command) = args.first().map(String::as_str) else {
+        eprintln!("A config command is required.");
+        usage();
+    };
+    let allowed: &[&str] = match command {
+        "init" => &[
+            "--config", "--user", "--token", "--title", "--sound", "--device", "--force",
+        ],
+        "validate" => &["--config", "--profile", "--online"],
+        "show" => &["--config", "--profile"],
+        "path" | "check" => &["--config"],
+        other => {
+            eprintln!("Unknown config command: {}", other);
+            usage();
+        }
+    };
+
+    let args = &args[1..];
+    let mut options = ConfigCommandOptions::default();
+    let mut i = 0;
+    while i < args.len() {
+        let arg = args[i].as_str();
+        if arg == "-h" || arg == "--help" {
+            usage();
+        }
+        if !allowed.contains(&arg) {
+            eprintln!("Invalid option {} for config {}", arg, command);
+            usage();
+        }
+        match arg {
+            "--config" | "--profile" => {
+                options.config.set(args, i);
+                i += 2;
+            }
+            "--online" => {
+                options.online = true;
+                i += 1;
+            }
+            "--force" => {
+                options.force = true;
+                i += 1;
+            }
+            _ => {
+                let value = Some(option_value(args, i).to_string());
+                match arg {
+                    "--user" => options.user = value,
+                    "--token" => options.token = value,
+                    "--title" => options.title = value,
+                    "--sound" => options.sound = value,
+                    _ => options.device = value,
+                }
+                i += 2;
+            }
+        }
+    }
+
+    match command {
+        "init" => config_init_command(options),
+        "validate" => config_validate_command(&options),
+        "show" => config_show_command(&options.config),
+        "path" => config_path_command(&options.config),
+        _ => config_check_command(&options.config),
+    }
+}
+
+/// Writes a new config file from the options, asking on the terminal for a
+/// user key or token not given.
+fn config_init_command(options: ConfigCommandOptions) {
+    let path = match &options.config.path {
+        Some(path) => PathBuf::from(path),
+        None => match user_config_paths().into_iter().next() {
+            Some(path) => path,
+            None => {
+                eprintln!("Cannot find a home directory; give the file with --config <path>.");
+                usage();
+            }
+        },
+    };
+    if path.exists() && !options.force {
+        fail(
+            "Error writing configuration",
+            Error::Config(format!(
+                "{} already exists (use --force to replace it)",
+                path.display()
+            )),
+        );
+    }
+
+    let interactive = io::stdin().is_terminal();
+    if !interactive && (options.user.is_none() || options.token.is_none()) {
+        eprintln!("--user and --token are required when standard input is not a terminal.");
+        usage();
+    }
+    let user = options
+        .user
+        .unwrap_or_else(|| prompt("Pushover user or group key"));
+    let token = options
+        .token
+        .unwrap_or_else(|| prompt("Pushover application token"));
+
+    let mut config = Config::default();
+    config.pushover.user = user;
+    config.pushover.token = token;
+    config.pushover.default_title = options.title;
+    if options.sound.is_some() || options.device.is_some() {
+        config.notification = Some(NotificationConfig {
+            sound: options.sound,
+            device: options.device,
+            ..Default::default()
+        });
+    }
+    if let Err(e) = config.validate() {
+        fail("Invalid configuration", e);
+    }
+
+    let content = match toml::to_string(&config) {
+        Ok(content) => content,
+        Err(e) => fail("Error writing configuration", Error::Config(e.to_string())),
+    };
+    if let Err(e) = write_private_file(&path, content.as_bytes()) {
+        fail(&format!("Error writing {}", path.display()), Error::Io(e));
+    }
+    println!("Wrote {}", path.display());
+}
+
+/// Reads one line from the terminal, exiting if the answer is empty.
+fn prompt(question: &str) -> String {
+    eprint!("{}: ", question);
+    let _ = io::stderr().flush();
+    let mut answer = String::new();
+    if let Err(e) = io::stdin().read_line(&mut answer) {
+        fail("Error reading answer", Error::Io(e));
+    }
+    let answer = answer.trim();
+    if answer.is_empty() {
+        eprintln!("{} is required.", question);
+        process::exit(1);
+    }
+    answer.to_string()
+}
+
+/// Creates `path` and its parent directories, readable by the owner only.
+fn write_private_file(path: &Path, content: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path
+        .parent()
+        .filter(|parent| !parent.as_os_str().is_empty())
+    {
+        fs::create_dir_all(parent)?;
+    }
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(content)
+}
+
+/// Checks the configuration for placeholders and malformed keys, and with
+/// `--online` asks Pushover whether the user key and device exist.
+fn config_validate_command(options: &ConfigCommandOptions) {
+    let config = options.config.load_or_exit(false);
+    if let Err(e) = config.validate() {
+        fail("Invalid configuration", e);
+    }
+
+    if options.online {
+        let client = build_client(&config, &ClientOptions::default());
+        let device = config
+            .notification
+            .as_ref()
+            .and_then(|notification| notification.device.as_deref());
+        match client.validate_user(client.user(), device) {
+            Ok(validation) => println!(
+                "{} key accepted by Pushover; devices: {}",
+                if validation.group { "Group" } else { "User" },
+                validation.devices.join(", ")
+            ),
+            Err(e) => fail("Error validating configuration", e),
+        }
+    }
+    println!("Configuration is valid");
+}
+
+/// Prints the merged configuration, with the selected profile applied and
+/// the keys redacted.
+fn config_show_command(options: &ConfigOptions) {
+    let config = options.load_or_exit(false);
+    match toml::to_string(&config.redacted()) {
+        Ok(content) => print!("{}", content),
+        Err(e) => fail("Error showing configuration", Error::Config(e.to_string())),
+    }
+}
+
+/// Prints every config file looked for, highest precedence first, and
+/// whether it is read.
+fn config_path_command(options: &ConfigOptions) {
+    let explicit = options.path.as_deref().map(Path::new);
+    let files = config_files(explicit).unwrap_or_default();
+    for path in config_search_paths(explicit) {
+        let status = if files.contains(&path) {
+            "read"
+        } else if path.exists() {
+            "not read"
+        } else {
+            "not found"
+        };
+        println!("{} ({})", path.display(), status);
+    }
+}
+
+/// Reports permission problems in every config file that would be read,
+/// exiting with the configuration error status if there are any.
+fn config_check_command(options: &ConfigOptions) {
+    let files = match config_files(options.path.as_deref().map(Path::new)) {
+        Ok(files) => files,
+        Err(e) => fail("Error checking configuration", e),
+    };
+    let mut found = false;
+    for file in &files {
+        let problems = match check_permissions(file) {
+            Ok(problems) => problems,
+            Err(e) => fail("Error checking configuration", e),
+        };
+        if problems.is_empty() {
+            println!("{}: ok", file.display());
+        }
+        for problem in problems {
+            println!("{}", problem);
+            println!("  fix with: {}", problem.fix);
+            found = true;
+        }
+    }
+
+    if found {
+        process::exit(EXIT_CONFIG);
+    }
+}
+
+/// Returns the `--max-wait` duration at `args[i]`, or exits with usage.
+fn max_wait_value(args: &[String], i: usize) -> Duration {
+    match option_value(args, i).parse::<u64>() {
+        Ok(seconds) if seconds > 0 => Duration::from_secs(seconds),
+        _ => {
+            eprintln!("Max wait must be a positive number of seconds.");
+            usage();
         }
     }
 }
+
+/// Blocks until the alert behind `receipt` is acknowledged or expires, and
+/// exits with [`EXIT_NOT_ACKNOWLEDGED`] if nobody acknowledged it, or with
+/// [`EXIT_TIMEOUT`] if it is still pending after `max_wait`.
+fn wait_for_ack(client: &Client, receipt: &str, max_wait: Option<Duration>, verbose: bool) {
+    if verbose {
+        eprintln!("Waiting for acknowledgement of receipt {}", receipt);
+    }
+
+    match client.wait_for_acknowledgement(receipt, MIN_POLL_INTERVAL, max_wait) {
+        Ok(status) if status.acknowledged => print_receipt(&status),
+        Ok(status) => {
+            print_receipt(&status);
+            eprintln!("Notification expired without being acknowledged.");
+            process::exit(EXIT_NOT_ACKNOWLEDGED);
+        }
+        Err(e) => fail("Error polling receipt", e),
+    }
+}
+
+fn print_receipt(status: &Receipt) {
+    let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+    println!("acknowledged: {}", yes_no(status.acknowledged));
+    if status.acknowledged {
+        println!("acknowledged_at: {}", status.acknowledged_at);
+        println!("acknowledged_by: {}", status.acknowledged_by);
+        println!("acknowledged_by_device: {}", status.acknowledged_by_device);
+    }
+    println!("last_delivered_at: {}", status.last_delivered_at);
+    println!("expired: {}", yes_no(status.expired));
+    println!("expires_at: {}", status.expires_at);
+}
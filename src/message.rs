@@ -0,0 +1,450 @@
+use crate::attachment::{base64_encode, AttachmentEncoding};
+use crate::{Attachment, Error};
+
+/// Maximum message length accepted by the API, in characters.
+pub const MAX_MESSAGE_LEN: usize = 1024;
+/// Maximum title length accepted by the API, in characters.
+pub const MAX_TITLE_LEN: usize = 250;
+/// Maximum supplementary URL length accepted by the API, in characters.
+pub const MAX_URL_LEN: usize = 512;
+/// Maximum supplementary URL title length accepted by the API, in characters.
+pub const MAX_URL_TITLE_LEN: usize = 100;
+/// Maximum device name length accepted by the API, in characters.
+pub const MAX_DEVICE_NAME_LEN: usize = 25;
+/// Priority that requires acknowledgement and needs `retry` and `expire`.
+pub const EMERGENCY_PRIORITY: i8 = 2;
+/// Shortest retry interval accepted for emergency-priority messages, in seconds.
+pub const MIN_RETRY: u32 = 30;
+/// Longest expiry accepted for emergency-priority messages, in seconds.
+pub const MAX_EXPIRE: u32 = 10800;
+
+/// A notification to send through [`Client::send`](crate::Client::send).
+///
+/// Every parameter of the messages API can be set through the builder
+/// methods. Call [`Message::validate`] to check the message locally; the
+/// client does so before anything goes on the wire.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Message {
+    pub(crate) message: String,
+    pub(crate) title: Option<String>,
+    pub(crate) priority: i8,
+    pub(crate) sound: Option<String>,
+    pub(crate) devices: Vec<String>,
+    pub(crate) url: Option<String>,
+    pub(crate) url_title: Option<String>,
+    pub(crate) html: bool,
+    pub(crate) monospace: bool,
+    pub(crate) timestamp: Option<u64>,
+    pub(crate) ttl: Option<u32>,
+    pub(crate) retry: Option<u32>,
+    pub(crate) expire: Option<u32>,
+    pub(crate) callback: Option<String>,
+    pub(crate) tags: Vec<String>,
+    pub(crate) attachment: Option<(Attachment, AttachmentEncoding)>,
+}
+
+impl Message {
+    pub fn new(message: impl Into<String>) -> Self {
+        Message {
+            message: message.into(),
+            ..Default::default()
+        }
+    }
+
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.title = Some(title.into());
+        self
+    }
+
+    pub fn priority(mut self, priority: i8) -> Self {
+        self.priority = priority;
+        self
+    }
+
+    pub fn sound(mut self, sound: impl Into<String>) -> Self {
+        self.sound = Some(sound.into());
+        self
+    }
+
+    /// Adds a device to deliver to, or several separated by commas. May be
+    /// called more than once.
+    pub fn device(mut self, device: impl Into<String>) -> Self {
+        self.devices.extend(split_devices(&device.into()));
+        self
+    }
+
+    /// Supplementary URL shown with the message.
+    pub fn url(mut self, url: impl Into<String>) -> Self {
+        self.url = Some(url.into());
+        self
+    }
+
+    /// Title shown for the supplementary URL instead of the URL itself.
+    pub fn url_title(mut self, url_title: impl Into<String>) -> Self {
+        self.url_title = Some(url_title.into());
+        self
+    }
+
+    /// Render the message body as HTML. Conflicts with `monospace`.
+    pub fn html(mut self, html: bool) -> Self {
+        self.html = html;
+        self
+    }
+
+    /// Render the message body in a monospace font. Conflicts with `html`.
+    pub fn monospace(mut self, monospace: bool) -> Self {
+        self.monospace = monospace;
+        self
+    }
+
+    /// Unix timestamp shown as the message time instead of the time received.
+    pub fn timestamp(mut self, timestamp: u64) -> Self {
+        self.timestamp = Some(timestamp);
+        self
+    }
+
+    /// Number of seconds after which the message is deleted from devices.
+    pub fn ttl(mut self, ttl: u32) -> Self {
+        self.ttl = Some(ttl);
+        self
+    }
+
+    /// How often, in seconds, an emergency-priority message is retried.
+    pub fn retry(mut self, retry: u32) -> Self {
+        self.retry = Some(retry);
+        self
+    }
+
+    /// How long, in seconds, an emergency-priority message keeps retrying.
+    pub fn expire(mut self, expire: u32) -> Self {
+        self.expire = Some(expire);
+        self
+    }
+
+    /// URL that Pushover calls once an emergency-priority message is acknowledged.
+    pub fn callback(mut self, callback: impl Into<String>) -> Self {
+        self.callback = Some(callback.into());
+        self
+    }
+
+    /// Adds a tag to an emergency-priority message. May be called more than once.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    /// Attaches an image, sent as a `multipart/form-data` file upload.
+    pub fn attachment(mut self, attachment: Attachment) -> Self {
+        self.attachment = Some((attachment, AttachmentEncoding::Multipart));
+        self
+    }
+
+    /// Attaches an image, sent base64-encoded in the `attachment_base64` field.
+    pub fn attachment_base64(mut self, attachment: Attachment) -> Self {
+        self.attachment = Some((attachment, AttachmentEncoding::Base64));
+        self
+    }
+
+    pub fn text(&self) -> &str {
+        &self.message
+    }
+
+    /// Checks field lengths and conflicting options against the API's rules.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.message.trim().is_empty() {
+            return Err(Error::Validation("Message must not be empty".to_string()));
+        }
+        check_len("message", &self.message, MAX_MESSAGE_LEN)?;
+        if let Some(title) = &self.title {
+            check_len("title", title, MAX_TITLE_LEN)?;
+        }
+        if let Some(url) = &self.url {
+            check_len("url", url, MAX_URL_LEN)?;
+        }
+        if let Some(url_title) = &self.url_title {
+            if self.url.is_none() {
+                return Err(Error::Validation(
+                    "url_title requires url to be set".to_string(),
+                ));
+            }
+            check_len("url_title", url_title, MAX_URL_TITLE_LEN)?;
+        }
+        if self.html && self.monospace {
+            return Err(Error::Validation(
+                "html and monospace cannot be used together".to_string(),
+            ));
+        }
+        if !(-2..=2).contains(&self.priority) {
+            return Err(Error::Validation(format!(
+                "Priority must be between -2 and 2, got {}",
+                self.priority
+            )));
+        }
+        if self.priority == EMERGENCY_PRIORITY {
+            let (retry, expire) = match (self.retry, self.expire) {
+                (Some(retry), Some(expire)) => (retry, expire),
+                _ => {
+                    return Err(Error::Validation(
+                        "Emergency priority (2) requires both retry and expire".to_string(),
+                    ))
+                }
+            };
+            if retry < MIN_RETRY {
+                return Err(Error::Validation(format!(
+                    "retry must be at least {} seconds, got {}",
+                    MIN_RETRY, retry
+                )));
+            }
+            if expire > MAX_EXPIRE {
+                return Err(Error::Validation(format!(
+                    "expire must be at most {} seconds, got {}",
+                    MAX_EXPIRE, expire
+                )));
+            }
+        }
+        if self.ttl == Some(0) {
+            return Err(Error::Validation(
+                "ttl must be a positive number of seconds".to_string(),
+            ));
+        }
+        for device in &self.devices {
+            check_device_name(device)?;
+        }
+        if let Some(callback) = &self.callback {
+            check_len("callback", callback, MAX_URL_LEN)?;
+        }
+        if let Some((attachment, _)) = &self.attachment {
+            attachment.validate()?;
+        }
+        Ok(())
+    }
+
+    /// Returns the message parameters as unencoded form fields.
+    pub(crate) fn form_fields(&self) -> Vec<(&'static str, String)> {
+        let mut fields = Vec::new();
+        if let Some(title) = &self.title {
+            fields.push(("title", title.clone()));
+        }
+        fields.push(("message", self.message.clone()));
+
+        // Add priority if not default
+        if self.priority != 0 {
+            fields.push(("priority", self.priority.to_string()));
+        }
+        if let Some(sound) = &self.sound {
+            fields.push(("sound", sound.clone()));
+        }
+        if !self.devices.is_empty() {
+            fields.push(("device", self.devices.join(",")));
+        }
+        if let Some(url) = &self.url {
+            fields.push(("url", url.clone()));
+        }
+        if let Some(url_title) = &self.url_title {
+            fields.push(("url_title", url_title.clone()));
+        }
+        if self.html {
+            fields.push(("html", "1".to_string()));
+        }
+        if self.monospace {
+            fields.push(("monospace", "1".to_string()));
+        }
+        if let Some(timestamp) = self.timestamp {
+            fields.push(("timestamp", timestamp.to_string()));
+        }
+        if let Some(ttl) = self.ttl {
+            fields.push(("ttl", ttl.to_string()));
+        }
+        if let Some(retry) = self.retry {
+            fields.push(("retry", retry.to_string()));
+        }
+        if let Some(expire) = self.expire {
+            fields.push(("expire", expire.to_string()));
+        }
+        if let Some(callback) = &self.callback {
+            fields.push(("callback", callback.clone()));
+        }
+        if !self.tags.is_empty() {
+            fields.push(("tags", self.tags.join(",")));
+        }
+        if let Some((attachment, AttachmentEncoding::Base64)) = &self.attachment {
+            fields.push(("attachment_base64", base64_encode(attachment.data())));
+            fields.push(("attachment_type", attachment.mime_type().to_string()));
+        }
+        fields
+    }
+}
+
+/// Default marker appended by [`truncate_message`] to show text was cut off.
+pub const DEFAULT_TRUNCATION_MARKER: &str = "…";
+
+/// Shortens `text` to at most `max_chars` characters, cutting on a character
+/// boundary and ending with `marker` when anything was removed.
+pub fn truncate_message(text: &str, max_chars: usize, marker: &str) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+
+    let marker_len = marker.chars().count();
+    if marker_len >= max_chars {
+        return text.chars().take(max_chars).collect();
+    }
+
+    let mut truncated: String = text.chars().take(max_chars - marker_len).collect();
+    truncated.push_str(marker);
+    truncated
+}
+
+pub(crate) fn check_len(field: &str, value: &str, max: usize) -> Result<(), Error> {
+    let len = value.chars().count();
+    if len > max {
+        return Err(Error::Validation(format!(
+            "{} is {} characters long, the maximum is {}",
+            field, len, max
+        )));
+    }
+    Ok(())
+}
+
+/// Splits a comma-separated list of device names.
+pub(crate) fn split_devices(devices: &str) -> impl Iterator<Item = String> + '_ {
+    devices.split(',').map(|device| device.trim().to_string())
+}
+
+/// Checks a device name against the API's limits: up to
+/// [`MAX_DEVICE_NAME_LEN`] letters, digits, `_` and `-`.
+pub(crate) fn check_device_name(device: &str) -> Result<(), Error> {
+    if device.is_empty() {
+        return Err(Error::Validation("device name is empty".to_string()));
+    }
+    check_len("device name", device, MAX_DEVICE_NAME_LEN)?;
+    if let Some(c) = device
+        .chars()
+        .find(|c| !(c.is_ascii_alphanumeric() || *c == '_' || *c == '-'))
+    {
+        return Err(Error::Validation(format!(
+            "device name '{}' contains '{}'; only letters, digits, _ and - are allowed",
+            device, c
+        )));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_form_fields_order_and_values() {
+        let message = Message::new("Disk full")
+            .title("Alert")
+            .priority(1)
+            .device("iphone")
+            .device("pixel")
+            .url("https://example.com/graph")
+            .url_title("Graph")
+            .html(true)
+            .timestamp(1_700_000_000)
+            .ttl(3600);
+
+        let fields = message.form_fields();
+        assert_eq!(
+            fields,
+            vec![
+                ("title", "Alert".to_string()),
+                ("message", "Disk full".to_string()),
+                ("priority", "1".to_string()),
+                ("device", "iphone,pixel".to_string()),
+                ("url", "https://example.com/graph".to_string()),
+                ("url_title", "Graph".to_string()),
+                ("html", "1".to_string()),
+                ("timestamp", "1700000000".to_string()),
+                ("ttl", "3600".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_validate_emergency_priority() {
+        let message = Message::new("Database down").priority(EMERGENCY_PRIORITY);
+        assert!(message.validate().is_err());
+
+        let message = message.retry(MIN_RETRY).expire(MAX_EXPIRE);
+        assert!(message.validate().is_ok());
+
+        let too_frequent = message.clone().retry(MIN_RETRY - 1);
+        assert!(too_frequent.validate().is_err());
+
+        let too_long = message.clone().expire(MAX_EXPIRE + 1);
+        assert!(too_long.validate().is_err());
+
+        let fields = message.callback("https://example.com/ack").form_fields();
+        assert!(fields.contains(&("retry", "30".to_string())));
+        assert!(fields.contains(&("expire", "10800".to_string())));
+        assert!(fields.contains(&("callback", "https://example.com/ack".to_string())));
+    }
+
+    #[test]
+    fn test_attachment_base64_fields() {
+        let image = Attachment::from_bytes("dot.png", "image/png", b"foo".to_vec());
+
+        let fields = Message::new("graph")
+            .attachment_base64(image.clone())
+            .form_fields();
+        assert!(fields.contains(&("attachment_base64", "Zm9v".to_string())));
+        assert!(fields.contains(&("attachment_type", "image/png".to_string())));
+
+        // Multipart attachments are not part of the text fields
+        let fields = Message::new("graph").attachment(image).form_fields();
+        assert!(fields
+            .iter()
+            .all(|(name, _)| !name.starts_with("attachment")));
+    }
+
+    #[test]
+    fn test_truncate_message() {
+        assert_eq!(truncate_message("short", 10, "..."), "short");
+        assert_eq!(truncate_message("exactly10!", 10, "..."), "exactly10!");
+        assert_eq!(
+            truncate_message("this is too long", 10, "..."),
+            "this is..."
+        );
+        assert_eq!(truncate_message("this is too long", 10, ""), "this is to");
+
+        // Multi-byte characters are never split
+        let truncated = truncate_message(&"é".repeat(20), 10, DEFAULT_TRUNCATION_MARKER);
+        assert_eq!(truncated, format!("{}…", "é".repeat(9)));
+
+        // A marker as long as the limit is dropped rather than exceeding it
+        assert_eq!(truncate_message("abcdef", 3, "[cut]"), "abc");
+    }
+
+    #[test]
+    fn test_device_names() {
+        let message = Message::new("hi").device("iphone, pixel").device("desktop");
+        assert_eq!(message.devices, vec!["iphone", "pixel", "desktop"]);
+        assert!(message.validate().is_ok());
+
+        let message = Message::new("hi").device("my phone");
+        assert_eq!(
+            message.validate().unwrap_err().to_string(),
+            "device name 'my phone' contains ' '; only letters, digits, _ and - are allowed"
+        );
+        let message = Message::new("hi").device("iphone,,pixel");
+        assert_eq!(
+            message.validate().unwrap_err().to_string(),
+            "device name is empty"
+        );
+        let message = Message::new("hi").device("d".repeat(MAX_DEVICE_NAME_LEN + 1));
+        assert!(message.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_counts_characters_not_bytes() {
+        let message = Message::new("é".repeat(MAX_MESSAGE_LEN));
+        assert!(message.validate().is_ok());
+
+        let message = Message::new("é".repeat(MAX_MESSAGE_LEN + 1));
+        assert!(message.validate().is_err());
+    }
+}
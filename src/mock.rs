@@ -0,0 +1,842 @@
+//! A local HTTPS stand-in for the Pushover API, for end-to-end tests.
+//!
+//! [`MockServer`] serves the messages, receipts, validate, sounds, groups and
+//! app limits endpoints with a freshly generated certificate, records every
+//! request it receives and can be scripted to fail. Point a
+//! [`Client`](crate::Client) at it with `with_api_url(&server.url())` and
+//! `with_ca_pem(server.ca_pem())`, or the CLI with `PUSHOVER_API_URL` and
+//! `PUSHOVER_CA_FILE`.
+//!
+//! Only available with the `mock` feature.
+
+use std::collections::VecDeque;
+use std::io::{self, Read, Write};
+use std::net::{Ipv4Addr, TcpListener, TcpStream, ToSocketAddrs};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use rcgen::{BasicConstraints, CertificateParams, DnType, IsCa, SanType};
+use rustls::{Certificate, PrivateKey, ServerConfig, ServerConnection, StreamOwned};
+use serde_json::json;
+
+use crate::BUILTIN_SOUNDS;
+
+/// Monthly message limit the mock reports in its `X-Limit-App-*` headers.
+const MOCK_APP_LIMIT: u64 = 10000;
+
+/// Custom sound the mock sounds endpoint lists besides the built-in ones.
+const MOCK_CUSTOM_SOUND: (&str, &str) = ("doorbell", "Doorbell (custom)");
+
+/// Devices every user has on the mock validate endpoint.
+const MOCK_DEVICES: &[&str] = &["iphone", "desktop"];
+
+/// A running mock Pushover API. The server stops when this is dropped.
+pub struct MockServer {
+    port: u16,
+    ca_pem: String,
+    state: Arc<Mutex<State>>,
+    shutdown: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+#[derive(Default)]
+struct State {
+    requests: Vec<RecordedRequest>,
+    responses: VecDeque<MockResponse>,
+    next_id: u64,
+    groups: Vec<MockGroup>,
+    /// Receipts cancelled before anyone acknowledged them.
+    canceled_receipts: Vec<String>,
+}
+
+/// A delivery group created through the mock's groups endpoints.
+struct MockGroup {
+    key: String,
+    name: String,
+    members: Vec<MockMember>,
+}
+
+struct MockMember {
+    user: String,
+    device: Option<String>,
+    memo: String,
+    disabled: bool,
+}
+
+/// A request received by a [`MockServer`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedRequest {
+    pub method: String,
+    /// Request path without the query string, e.g. `/1/messages.json`.
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    /// Fields from the query string and the form body, in the order sent.
+    pub fields: Vec<(String, String)>,
+    /// File parts of a `multipart/form-data` body.
+    pub files: Vec<RecordedFile>,
+    pub body: Vec<u8>,
+}
+
+impl RecordedRequest {
+    /// Returns the first field named `name`.
+    pub fn field(&self, name: &str) -> Option<&str> {
+        self.fields
+            .iter()
+            .find(|(key, _)| key == name)
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Returns the first header named `name`, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// A file part of a recorded `multipart/form-data` request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecordedFile {
+    pub name: String,
+    pub filename: String,
+    pub content_type: String,
+    pub data: Vec<u8>,
+}
+
+/// A scripted reply, see [`MockServer::enqueue`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MockResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+impl MockResponse {
+    /// A response with a JSON body.
+    pub fn new(status: u16, body: impl Into<String>) -> Self {
+        MockResponse {
+            status,
+            headers: Vec::new(),
+            body: body.into(),
+        }
+    }
+
+    /// Adds a header. With `Transfer-Encoding: chunked` the body is sent in
+    /// chunks instead of with a `Content-Length`.
+    pub fn with_header(mut self, name: impl Into<String>, value: impl Into<String>) -> Self {
+        self.headers.push((name.into(), value.into()));
+        self
+    }
+
+    /// A rejection in the format Pushover uses, with `"status": 0`.
+    pub fn api_error(status: u16, errors: &[&str]) -> Self {
+        MockResponse::new(
+            status,
+            json!({ "status": 0, "errors": errors, "request": "mock-error" }).to_string(),
+        )
+    }
+
+    /// The HTTP 429 Pushover returns once the app's monthly limit is used up.
+    pub fn rate_limited() -> Self {
+        MockResponse::api_error(429, &["application has exceeded its monthly message limit"])
+            .with_header("X-Limit-App-Limit", MOCK_APP_LIMIT.to_string())
+            .with_header("X-Limit-App-Remaining", "0")
+            .with_header("X-Limit-App-Reset", limit_reset().to_string())
+    }
+}
+
+impl MockServer {
+    /// Starts a server on an ephemeral port on the loopback interface.
+    pub fn start() -> Result<Self, Box<dyn std::error::Error>> {
+        MockServer::bind((Ipv4Addr::LOCALHOST, 0))
+    }
+
+    /// Starts a server on the given address.
+    pub fn bind(addr: impl ToSocketAddrs) -> Result<Self, Box<dyn std::error::Error>> {
+        let (ca_pem, chain, key) = generate_certificates()?;
+        let tls_config = Arc::new(
+            ServerConfig::builder()
+                .with_safe_defaults()
+                .with_no_client_auth()
+                .with_single_cert(chain, key)?,
+        );
+
+        let listener = TcpListener::bind(addr)?;
+        let port = listener.local_addr()?.port();
+        let state = Arc::new(Mutex::new(State::default()));
+        let shutdown = Arc::new(AtomicBool::new(false));
+
+        let handle = {
+            let state = Arc::clone(&state);
+            let shutdown = Arc::clone(&shutdown);
+            thread::spawn(move || {
+                for stream in listener.incoming() {
+                    if shutdown.load(Ordering::SeqCst) {
+                        break;
+                    }
+                    let Ok(stream) = stream else { continue };
+                    let tls_config = Arc::clone(&tls_config);
+                    let state = Arc::clone(&state);
+                    thread::spawn(move || {
+                        // A client that hangs up mid-request only affects itself
+                        let _ = handle_connection(stream, tls_config, &state);
+                    });
+                }
+            })
+        };
+
+        Ok(MockServer {
+            port,
+            ca_pem,
+            state,
+            shutdown,
+            handle: Some(handle),
+        })
+    }
+
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// API base URL to pass to [`Client::with_api_url`](crate::Client::with_api_url).
+    pub fn url(&self) -> String {
+        format!("https://localhost:{}/1", self.port)
+    }
+
+    /// PEM certificate of the CA that signed the server certificate.
+    pub fn ca_pem(&self) -> &str {
+        &self.ca_pem
+    }
+
+    /// Every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<RecordedRequest> {
+        self.lock().requests.clone()
+    }
+
+    pub fn last_request(&self) -> Option<RecordedRequest> {
+        self.lock().requests.last().cloned()
+    }
+
+    /// Serves `response` to the next request instead of the default reply.
+    /// Queued responses are used in order, one per request.
+    pub fn enqueue(&self, response: MockResponse) {
+        self.lock().responses.push_back(response);
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, State> {
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl Drop for MockServer {
+    fn drop(&mut self) {
+        self.shutdown.store(true, Ordering::SeqCst);
+        // Wake the accept loop so it sees the flag
+        let _ = TcpStream::connect((Ipv4Addr::LOCALHOST, self.port));
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+fn generate_certificates() -> Result<(String, Vec<Certificate>, PrivateKey), rcgen::Error> {
+    let mut ca_params = CertificateParams::new(Vec::new());
+    ca_params.is_ca = IsCa::Ca(BasicConstraints::Unconstrained);
+    ca_params
+        .distinguished_name
+        .push(DnType::CommonName, "pushover-mock CA");
+    let ca = rcgen::Certificate::from_params(ca_params)?;
+
+    let mut params = CertificateParams::new(vec!["localhost".to_string()]);
+    params
+        .subject_alt_names
+        .push(SanType::IpAddress(Ipv4Addr::LOCALHOST.into()));
+    params
+        .distinguished_name
+        .push(DnType::CommonName, "localhost");
+    let server = rcgen::Certificate::from_params(params)?;
+
+    Ok((
+        ca.serialize_pem()?,
+        vec![Certificate(server.serialize_der_with_signer(&ca)?)],
+        PrivateKey(server.serialize_private_key_der()),
+    ))
+}
+
+fn handle_connection(
+    stream: TcpStream,
+    tls_config: Arc<ServerConfig>,
+    state: &Mutex<State>,
+) -> io::Result<()> {
+    stream.set_read_timeout(Some(Duration::from_secs(10)))?;
+    let conn = ServerConnection::new(tls_config).map_err(io::Error::other)?;
+    let mut tls = StreamOwned::new(conn, stream);
+
+    let request = read_request(&mut tls)?;
+    let response = {
+        let mut state = state.lock().unwrap_or_else(|e| e.into_inner());
+        let response = match state.responses.pop_front() {
+            Some(response) => response,
+            None => default_response(&mut state, &request),
+        };
+        state.requests.push(request);
+        response
+    };
+
+    tls.write_all(&serialize_response(&response))?;
+    tls.conn.send_close_notify();
+    tls.flush()
+}
+
+fn read_request(stream: &mut impl Read) -> io::Result<RecordedRequest> {
+    let mut data = Vec::new();
+    let mut chunk = [0u8; 8192];
+    let header_end = loop {
+        if let Some(pos) = find(&data, b"\r\n\r\n", 0) {
+            break pos;
+        }
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        data.extend_from_slice(&chunk[..n]);
+    };
+
+    let head = String::from_utf8_lossy(&data[..header_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or("").split_whitespace();
+    let method = request_line.next().unwrap_or("").to_string();
+    let target = request_line.next().unwrap_or("/").to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = data[header_end + 4..].to_vec();
+    while body.len() < content_length {
+        let n = stream.read(&mut chunk)?;
+        if n == 0 {
+            return Err(io::ErrorKind::UnexpectedEof.into());
+        }
+        body.extend_from_slice(&chunk[..n]);
+    }
+    body.truncate(content_length);
+
+    let (path, query) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), query),
+        None => (target.clone(), ""),
+    };
+    let mut fields = parse_urlencoded(query);
+    let mut files = Vec::new();
+
+    let content_type = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("Content-Type"))
+        .map(|(_, value)| value.as_str())
+        .unwrap_or("");
+    if content_type.starts_with("application/x-www-form-urlencoded") {
+        fields.extend(parse_urlencoded(&String::from_utf8_lossy(&body)));
+    } else if let Some(boundary) = content_type
+        .strip_prefix("multipart/form-data")
+        .and_then(|params| params.split_once("boundary="))
+        .map(|(_, boundary)| boundary.trim_matches('"'))
+    {
+        let (text, file_parts) = parse_multipart(&body, boundary);
+        fields.extend(text);
+        files = file_parts;
+    }
+
+    Ok(RecordedRequest {
+        method,
+        path,
+        headers,
+        fields,
+        files,
+        body,
+    })
+}
+
+fn default_response(state: &mut State, request: &RecordedRequest) -> MockResponse {
+    if request.field("token").is_none_or(str::is_empty) {
+        return MockResponse::api_error(400, &["application token is invalid"]);
+    }
+
+    state.next_id += 1;
+    let id = state.next_id;
+    let request_id = format!("mock-request-{}", id);
+    let path = request.path.strip_prefix("/1/").unwrap_or(&request.path);
+
+    match (request.method.as_str(), path) {
+        ("POST", "messages.json") => {
+            if request.field("user").is_none_or(str::is_empty) {
+                return MockResponse::api_error(400, &["user identifier is invalid"]);
+            }
+            if request.field("message").is_none_or(str::is_empty) {
+                return MockResponse::api_error(400, &["message cannot be blank"]);
+            }
+            let mut body = json!({ "status": 1, "request": request_id });
+            if request.field("priority") == Some("2") {
+                body["receipt"] = json!(format!("mockreceipt{:019}", id));
+            }
+            // This message is not recorded yet
+            let remaining = remaining_messages(state).saturating_sub(1);
+            MockResponse::new(200, body.to_string())
+                .with_header("X-Limit-App-Limit", MOCK_APP_LIMIT.to_string())
+                .with_header("X-Limit-App-Remaining", remaining.to_string())
+                .with_header("X-Limit-App-Reset", limit_reset().to_string())
+        }
+        ("GET", "apps/limits.json") => MockResponse::new(
+            200,
+            json!({
+                "status": 1,
+                "limit": MOCK_APP_LIMIT,
+                "remaining": remaining_messages(state),
+                "reset": limit_reset(),
+                "request": request_id,
+            })
+            .to_string(),
+        ),
+        ("POST", "users/validate.json") => {
+            if let Some(device) = request.field("device").filter(|d| !d.is_empty()) {
+                if !MOCK_DEVICES.contains(&device) {
+                    return MockResponse::api_error(400, &["device name is not valid for user"]);
+                }
+            }
+            MockResponse::new(
+                200,
+                json!({
+                    "status": 1,
+                    "group": 0,
+                    "devices": MOCK_DEVICES,
+                    "licenses": ["iOS"],
+                    "request": request_id,
+                })
+                .to_string(),
+            )
+        }
+        ("GET", "sounds.json") => {
+            let sounds: serde_json::Map<String, serde_json::Value> = BUILTIN_SOUNDS
+                .iter()
+                .chain([&MOCK_CUSTOM_SOUND])
+                .map(|(name, description)| (name.to_string(), json!(description)))
+                .collect();
+            MockResponse::new(
+                200,
+                json!({ "status": 1, "sounds": sounds, "request": request_id }).to_string(),
+            )
+        }
+        ("POST", "groups.json") => {
+            let Some(name) = request.field("name").filter(|n| !n.is_empty()) else {
+                return MockResponse::api_error(400, &["name is invalid"]);
+            };
+            let key = format!("gmockgroup{:020}", id);
+            state.groups.push(MockGroup {
+                key: key.clone(),
+                name: name.to_string(),
+                members: Vec::new(),
+            });
+            MockResponse::new(
+                200,
+                json!({ "status": 1, "group": key, "request": request_id }).to_string(),
+            )
+        }
+        ("GET", "groups.json") => {
+            let groups: Vec<_> = state
+                .groups
+                .iter()
+                .map(|g| json!({ "group": g.key, "name": g.name }))
+                .collect();
+            MockResponse::new(
+                200,
+                json!({ "status": 1, "groups": groups, "request": request_id }).to_string(),
+            )
+        }
+        (method, path) if path.starts_with("groups/") => {
+            let path = path.trim_start_matches("groups/").trim_end_matches(".json");
+            let (key, action) = path.split_once('/').unwrap_or((path, ""));
+            match state.groups.iter_mut().find(|g| g.key == url_decode(key)) {
+                Some(group) => group_response(group, method, action, request, request_id),
+                None => MockResponse::api_error(
+                    404,
+                    &["group not found or you are not authorized to edit it"],
+                ),
+            }
+        }
+        ("POST", path) if path.starts_with("receipts/cancel_by_tag/") => {
+            let tag = url_decode(
+                path.trim_start_matches("receipts/cancel_by_tag/")
+                    .trim_end_matches(".json"),
+            );
+            let canceled = state
+                .requests
+                .iter()
+                .filter(|r| r.field("priority") == Some("2"))
+                .filter(|r| {
+                    r.field("tags")
+                        .is_some_and(|tags| tags.split(',').any(|t| t == tag))
+                })
+                .count();
+            MockResponse::new(
+                200,
+                json!({ "status": 1, "canceled": canceled, "request": request_id }).to_string(),
+            )
+        }
+        ("POST", path) if path.starts_with("receipts/") && path.ends_with("/cancel.json") => {
+            let receipt = path
+                .trim_start_matches("receipts/")
+                .trim_end_matches("/cancel.json");
+            state.canceled_receipts.push(url_decode(receipt));
+            MockResponse::new(
+                200,
+                json!({ "status": 1, "request": request_id }).to_string(),
+            )
+        }
+        ("GET", path) if path.starts_with("receipts/") && path.ends_with(".json") => {
+            let now = unix_time();
+            let receipt = url_decode(
+                path.trim_start_matches("receipts/")
+                    .trim_end_matches(".json"),
+            );
+            // A cancelled alert stays unacknowledged until it would have expired
+            if state.canceled_receipts.contains(&receipt) {
+                return MockResponse::new(
+                    200,
+                    json!({
+                        "status": 1,
+                        "acknowledged": 0,
+                        "last_delivered_at": now,
+                        "expired": 0,
+                        "expires_at": now + 3600,
+                        "request": request_id,
+                    })
+                    .to_string(),
+                );
+            }
+            MockResponse::new(
+                200,
+                json!({
+                    "status": 1,
+                    "acknowledged": 1,
+                    "acknowledged_at": now,
+                    "acknowledged_by": "mockuser",
+                    "acknowledged_by_device": MOCK_DEVICES[0],
+                    "last_delivered_at": now,
+                    "expired": 0,
+                    "expires_at": now + 3600,
+                    "called_back": 0,
+                    "called_back_at": 0,
+                    "request": request_id,
+                })
+                .to_string(),
+            )
+        }
+        _ => MockResponse::api_error(404, &["resource not found"]),
+    }
+}
+
+/// Handles `groups/<key>.json` and the `groups/<key>/<action>.json` endpoints.
+fn group_response(
+    group: &mut MockGroup,
+    method: &str,
+    action: &str,
+    request: &RecordedRequest,
+    request_id: String,
+) -> MockResponse {
+    let ok = || {
+        MockResponse::new(
+            200,
+            json!({ "status": 1, "request": request_id }).to_string(),
+        )
+    };
+    if method == "GET" && action.is_empty() {
+        let users: Vec<_> = group
+            .members
+            .iter()
+            .map(|m| {
+                json!({
+                    "user": m.user,
+                    "device": m.device,
+                    "memo": m.memo,
+                    "disabled": m.disabled,
+                })
+            })
+            .collect();
+        return MockResponse::new(
+            200,
+            json!({
+                "status": 1,
+                "name": group.name,
+                "users": users,
+                "request": request_id,
+            })
+            .to_string(),
+        );
+    }
+    if method != "POST" {
+        return MockResponse::api_error(404, &["resource not found"]);
+    }
+    if action == "rename" {
+        return match request.field("name").filter(|n| !n.is_empty()) {
+            Some(name) => {
+                group.name = name.to_string();
+                ok()
+            }
+            None => MockResponse::api_error(400, &["name is invalid"]),
+        };
+    }
+
+    let Some(user) = request.field("user").filter(|u| !u.is_empty()) else {
+        return MockResponse::api_error(400, &["user key is invalid"]);
+    };
+    let device = request.field("device").filter(|d| !d.is_empty());
+    let position = group
+        .members
+        .iter()
+        .position(|m| m.user == user && (device.is_none() || m.device.as_deref() == device));
+    match (action, position) {
+        ("add_user", Some(_)) => {
+            MockResponse::api_error(400, &["user is already a member of this group"])
+        }
+        ("add_user", None) => {
+            group.members.push(MockMember {
+                user: user.to_string(),
+                device: device.map(str::to_string),
+                memo: request.field("memo").unwrap_or_default().to_string(),
+                disabled: false,
+            });
+            ok()
+        }
+        ("delete_user" | "disable_user" | "enable_user", None) => {
+            MockResponse::api_error(400, &["user is not a member of this group"])
+        }
+        ("delete_user", Some(position)) => {
+            group.members.remove(position);
+            ok()
+        }
+        ("disable_user" | "enable_user", Some(position)) => {
+            group.members[position].disabled = action == "disable_user";
+            ok()
+        }
+        _ => MockResponse::api_error(404, &["resource not found"]),
+    }
+}
+
+fn serialize_response(response: &MockResponse) -> Vec<u8> {
+    let reason = match response.status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        404 => "Not Found",
+        429 => "Too Many Requests",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "Unknown",
+    };
+    let mut head = format!("HTTP/1.1 {} {}\r\n", response.status, reason);
+    if !response
+        .headers
+        .iter()
+        .any(|(name, _)| name.eq_ignore_ascii_case("Content-Type"))
+    {
+        head.push_str("Content-Type: application/json; charset=utf-8\r\n");
+    }
+    for (name, value) in &response.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    let chunked = response.headers.iter().any(|(name, value)| {
+        name.eq_ignore_ascii_case("Transfer-Encoding") && value.eq_ignore_ascii_case("chunked")
+    });
+    if !chunked {
+        head.push_str(&format!("Content-Length: {}\r\n", response.body.len()));
+    }
+    head.push_str("Connection: close\r\n\r\n");
+
+    let mut bytes = head.into_bytes();
+    if chunked {
+        // Small chunks so that clients have to put several together
+        for chunk in response.body.as_bytes().chunks(16) {
+            bytes.extend_from_slice(format!("{:x}\r\n", chunk.len()).as_bytes());
+            bytes.extend_from_slice(chunk);
+            bytes.extend_from_slice(b"\r\n");
+        }
+        bytes.extend_from_slice(b"0\r\n\r\n");
+    } else {
+        bytes.extend_from_slice(response.body.as_bytes());
+    }
+    bytes
+}
+
+fn parse_urlencoded(input: &str) -> Vec<(String, String)> {
+    input
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (name, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (url_decode(name), url_decode(value))
+        })
+        .collect()
+}
+
+fn url_decode(input: &str) -> String {
+    let bytes = input.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'+' => decoded.push(b' '),
+            b'%' if i + 2 < bytes.len() => {
+                let hex = |b: u8| (b as char).to_digit(16);
+                match (hex(bytes[i + 1]), hex(bytes[i + 2])) {
+                    (Some(high), Some(low)) => {
+                        decoded.push((high * 16 + low) as u8);
+                        i += 2;
+                    }
+                    _ => decoded.push(b'%'),
+                }
+            }
+            byte => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).into_owned()
+}
+
+fn parse_multipart(body: &[u8], boundary: &str) -> (Vec<(String, String)>, Vec<RecordedFile>) {
+    let delimiter = format!("--{}", boundary).into_bytes();
+    let mut fields = Vec::new();
+    let mut files = Vec::new();
+
+    let Some(mut pos) = find(body, &delimiter, 0) else {
+        return (fields, files);
+    };
+    loop {
+        let start = pos + delimiter.len();
+        // The closing delimiter is followed by "--"
+        if body[start..].starts_with(b"--") {
+            break;
+        }
+        let Some(next) = find(body, &delimiter, start) else {
+            break;
+        };
+        let part = &body[start..next];
+        let part = part.strip_prefix(b"\r\n").unwrap_or(part);
+        let part = part.strip_suffix(b"\r\n").unwrap_or(part);
+
+        if let Some(header_end) = find(part, b"\r\n\r\n", 0) {
+            let head = String::from_utf8_lossy(&part[..header_end]);
+            let data = &part[header_end + 4..];
+            let mut name = String::new();
+            let mut filename = None;
+            let mut content_type = String::new();
+            for line in head.split("\r\n") {
+                let Some((header, value)) = line.split_once(':') else {
+                    continue;
+                };
+                if header.eq_ignore_ascii_case("Content-Disposition") {
+                    name = disposition_param(value, "name").unwrap_or_default();
+                    filename = disposition_param(value, "filename");
+                } else if header.eq_ignore_ascii_case("Content-Type") {
+                    content_type = value.trim().to_string();
+                }
+            }
+            match filename {
+                Some(filename) => files.push(RecordedFile {
+                    name,
+                    filename,
+                    content_type,
+                    data: data.to_vec(),
+                }),
+                None => fields.push((name, String::from_utf8_lossy(data).into_owned())),
+            }
+        }
+        pos = next;
+    }
+    (fields, files)
+}
+
+fn disposition_param(value: &str, param: &str) -> Option<String> {
+    value.split(';').map(str::trim).find_map(|item| {
+        let (key, value) = item.split_once('=')?;
+        (key == param).then(|| value.trim_matches('"').to_string())
+    })
+}
+
+fn find(haystack: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    haystack
+        .get(from..)?
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| pos + from)
+}
+
+fn unix_time() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0)
+}
+
+/// What is left of the monthly limit after the messages received so far.
+fn remaining_messages(state: &State) -> u64 {
+    let sent = state
+        .requests
+        .iter()
+        .filter(|r| r.method == "POST" && r.path == "/1/messages.json")
+        .count() as u64;
+    MOCK_APP_LIMIT.saturating_sub(sent)
+}
+
+// Pushover resets limits monthly; a fixed 30 days ahead is close enough here
+fn limit_reset() -> u64 {
+    unix_time() + 30 * 24 * 60 * 60
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_url_decode() {
+        assert_eq!(url_decode("Hello+World%21"), "Hello World!");
+        assert_eq!(url_decode("caf%C3%A9"), "café");
+        assert_eq!(url_decode("100%"), "100%");
+        assert_eq!(
+            parse_urlencoded("token=abc&message=a%26b&empty="),
+            vec![
+                ("token".to_string(), "abc".to_string()),
+                ("message".to_string(), "a&b".to_string()),
+                ("empty".to_string(), String::new()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_multipart() {
+        let attachment =
+            crate::Attachment::from_bytes("graph.png", "image/png", b"\x89PNG\r\n--".to_vec());
+        let fields = vec![("token", "abc".to_string()), ("message", "hi".to_string())];
+        let body = crate::attachment::multipart_body("XyZ", &fields, &attachment);
+
+        let (fields, files) = parse_multipart(&body, "XyZ");
+        assert_eq!(
+            fields,
+            vec![
+                ("token".to_string(), "abc".to_string()),
+                ("message".to_string(), "hi".to_string()),
+            ]
+        );
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].name, "attachment");
+        assert_eq!(files[0].filename, "graph.png");
+        assert_eq!(files[0].content_type, "image/png");
+        assert_eq!(files[0].data, b"\x89PNG\r\n--");
+    }
+}
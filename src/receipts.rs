@@ -0,0 +1,138 @@
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Deserializer};
+
+use crate::{url_encode, Client, Error};
+
+/// Shortest interval at which Pushover allows receipts to be polled.
+pub const MIN_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Delivery and acknowledgement status of an emergency-priority message.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Receipt {
+    #[serde(deserialize_with = "int_bool")]
+    pub acknowledged: bool,
+    /// Unix timestamp of the acknowledgement, 0 if not acknowledged.
+    #[serde(default)]
+    pub acknowledged_at: u64,
+    /// User key of the user who acknowledged the message.
+    #[serde(default)]
+    pub acknowledged_by: String,
+    #[serde(default)]
+    pub acknowledged_by_device: String,
+    /// Unix timestamp of the last delivery attempt.
+    #[serde(default)]
+    pub last_delivered_at: u64,
+    /// Whether the message stopped retrying without being acknowledged in time.
+    #[serde(deserialize_with = "int_bool")]
+    pub expired: bool,
+    #[serde(default)]
+    pub expires_at: u64,
+    #[serde(default, deserialize_with = "int_bool")]
+    pub called_back: bool,
+    #[serde(default)]
+    pub called_back_at: u64,
+}
+
+#[derive(Deserialize)]
+struct CancelResponse {
+    #[serde(default)]
+    canceled: u32,
+}
+
+// The receipts API reports flags as 0/1 integers
+pub(crate) fn int_bool<'de, D: Deserializer<'de>>(deserializer: D) -> Result<bool, D::Error> {
+    Ok(u8::deserialize(deserializer)? != 0)
+}
+
+impl Client {
+    /// Fetches the status of an emergency-priority message.
+    pub fn receipt(&self, receipt: &str) -> Result<Receipt, Error> {
+        self.get(&format!("receipts/{}.json", url_encode(receipt)))
+    }
+
+    /// Stops retrying an emergency-priority message.
+    pub fn cancel_receipt(&self, receipt: &str) -> Result<(), Error> {
+        let _: CancelResponse = self.post(
+            &format!("receipts/{}/cancel.json", url_encode(receipt)),
+            Vec::new(),
+        )?;
+        Ok(())
+    }
+
+    /// Stops retrying every emergency-priority message sent with `tag`.
+    /// Returns the number of messages cancelled.
+    pub fn cancel_by_tag(&self, tag: &str) -> Result<u32, Error> {
+        let response: CancelResponse = self.post(
+            &format!("receipts/cancel_by_tag/{}.json", url_encode(tag)),
+            Vec::new(),
+        )?;
+        Ok(response.canceled)
+    }
+
+    /// Polls a receipt until the message is acknowledged or expires, or
+    /// until `max_wait` has passed if one is given.
+    ///
+    /// The interval is raised to [`MIN_POLL_INTERVAL`] if shorter. Check
+    /// [`Receipt::acknowledged`] on the result to tell acknowledged from
+    /// expired. A receipt still pending after `max_wait`, for example one
+    /// cancelled without being acknowledged, gives [`Error::Timeout`].
+    pub fn wait_for_acknowledgement(
+        &self,
+        receipt: &str,
+        poll_interval: Duration,
+        max_wait: Option<Duration>,
+    ) -> Result<Receipt, Error> {
+        let poll_interval = poll_interval.max(MIN_POLL_INTERVAL);
+        let started = Instant::now();
+        loop {
+            let status = self.receipt(receipt)?;
+            if status.acknowledged || status.expired {
+                return Ok(status);
+            }
+            let sleep = match max_wait.map(|max_wait| max_wait.saturating_sub(started.elapsed())) {
+                Some(remaining) if remaining.is_zero() => {
+                    return Err(Error::Timeout(format!(
+                        "Gave up waiting for acknowledgement of receipt {} after {}s",
+                        receipt,
+                        started.elapsed().as_secs()
+                    )))
+                }
+                Some(remaining) => poll_interval.min(remaining),
+                None => poll_interval,
+            };
+            thread::sleep(sleep);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_receipt_deserialize() {
+        let body = r#"{
+            "status": 1,
+            "acknowledged": 1,
+            "acknowledged_at": 1360019238,
+            "acknowledged_by": "uQiRzpo4DXghDmr9QzzfQu27cmVRsG",
+            "acknowledged_by_device": "iphone",
+            "last_delivered_at": 1360001238,
+            "expired": 0,
+            "expires_at": 1360019290,
+            "called_back": 0,
+            "called_back_at": 0,
+            "request": "6g890a90-7943-4f3d-ab86-5c9ec2a5ab72"
+        }"#;
+
+        let receipt: Receipt = serde_json::from_str(body).unwrap();
+        assert!(receipt.acknowledged);
+        assert!(!receipt.expired);
+        assert!(!receipt.called_back);
+        assert_eq!(receipt.acknowledged_by, "uQiRzpo4DXghDmr9QzzfQu27cmVRsG");
+        assert_eq!(receipt.acknowledged_by_device, "iphone");
+        assert_eq!(receipt.last_delivered_at, 1360001238);
+    }
+}
@@ -0,0 +1,136 @@
+use std::fmt;
+
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+
+use crate::{AppLimits, Error};
+
+/// The body Pushover returns for an accepted message.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct SendResponse {
+    pub status: i32,
+    /// Request id assigned by Pushover, useful when contacting support.
+    pub request: String,
+    /// Receipt id, only present for emergency-priority messages.
+    #[serde(default)]
+    pub receipt: Option<String>,
+    /// The application's message quota after this message, from the
+    /// response headers.
+    #[serde(skip)]
+    pub limits: Option<AppLimits>,
+}
+
+/// A request that Pushover rejected, with the error messages it returned.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiError {
+    pub http_status: u16,
+    pub request: Option<String>,
+    pub errors: Vec<String>,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Pushover API error (HTTP {})", self.http_status)?;
+        if !self.errors.is_empty() {
+            write!(f, ": {}", self.errors.join("; "))?;
+        }
+        if let Some(request) = &self.request {
+            write!(f, " [request {}]", request)?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
+#[derive(Deserialize)]
+struct ErrorBody {
+    #[serde(default)]
+    request: Option<String>,
+    #[serde(default)]
+    errors: Vec<String>,
+}
+
+/// Interprets an API response body.
+///
+/// A response is successful when the HTTP status is 200 and the JSON body has
+/// `"status": 1`. Otherwise the `errors` array is returned as an [`ApiError`],
+/// classified as [`Error::RateLimited`] for HTTP 429 and [`Error::Api`] otherwise.
+pub(crate) fn parse_api_response<T: DeserializeOwned>(
+    http_status: u16,
+    body: &[u8],
+) -> Result<T, Error> {
+    let value: serde_json::Value = match serde_json::from_slice(body) {
+        Ok(value) => value,
+        Err(_) => {
+            return Err(Error::Http {
+                status: http_status,
+                body: String::from_utf8_lossy(body).trim().to_string(),
+            })
+        }
+    };
+
+    if http_status == 200 && value.get("status").and_then(|s| s.as_i64()) == Some(1) {
+        return serde_json::from_value(value)
+            .map_err(|e| Error::invalid_data(format!("Unexpected response from Pushover: {}", e)));
+    }
+
+    let body: ErrorBody = serde_json::from_value(value).unwrap_or(ErrorBody {
+        request: None,
+        errors: Vec::new(),
+    });
+    Err(Error::from(ApiError {
+        http_status,
+        request: body.request,
+        errors: body.errors,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_success() {
+        let body = br#"{"status":1,"request":"647d2300-702c-4b38-8b2f-d56326ae460b"}"#;
+        let response: SendResponse = parse_api_response(200, body).unwrap();
+        assert_eq!(response.status, 1);
+        assert_eq!(response.request, "647d2300-702c-4b38-8b2f-d56326ae460b");
+        assert!(response.receipt.is_none());
+
+        let body = br#"{"status":1,"request":"abc","receipt":"rcpt123"}"#;
+        let response: SendResponse = parse_api_response(200, body).unwrap();
+        assert_eq!(response.receipt, Some("rcpt123".to_string()));
+    }
+
+    #[test]
+    fn test_parse_api_errors() {
+        let body = br#"{"token":"invalid","errors":["application token is invalid"],"status":0,"request":"5042853c"}"#;
+        let err = parse_api_response::<SendResponse>(400, body).unwrap_err();
+        let Error::Api(api_error) = &err else {
+            panic!("expected an API error, got {:?}", err);
+        };
+        assert_eq!(api_error.http_status, 400);
+        assert_eq!(api_error.request, Some("5042853c".to_string()));
+        assert_eq!(api_error.errors, vec!["application token is invalid"]);
+        assert_eq!(
+            err.to_string(),
+            "Pushover API error (HTTP 400): application token is invalid [request 5042853c]"
+        );
+    }
+
+    #[test]
+    fn test_parse_rate_limited() {
+        let body =
+            br#"{"status":0,"errors":["application has exceeded its monthly message limit"]}"#;
+        let err = parse_api_response::<SendResponse>(429, body).unwrap_err();
+        assert!(matches!(err, Error::RateLimited(ref e) if e.http_status == 429));
+    }
+
+    #[test]
+    fn test_parse_non_json_body() {
+        let err = parse_api_response::<SendResponse>(502, b"Bad Gateway").unwrap_err();
+        assert!(matches!(err, Error::Http { status: 502, .. }));
+        assert!(err.to_string().contains("502"));
+    }
+}
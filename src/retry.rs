@@ -0,0 +1,184 @@
+use std::io;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::Error;
+
+/// Default number of retries after a failed first attempt.
+pub const DEFAULT_RETRIES: u32 = 3;
+
+/// When and how often a [`Client`](crate::Client) repeats a request that
+/// failed for a reason that may pass: a connection error, a timeout, an HTTP
+/// 5xx or a 429. Requests Pushover rejected as invalid are never repeated,
+/// and all attempts together stay within the overall
+/// [`Timeouts`](crate::Timeouts).
+///
+/// A request that timed out may have reached Pushover, so a retried message
+/// can occasionally be delivered twice.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    /// Attempts after the first one; 0 disables retrying.
+    pub max_retries: u32,
+    /// Delay before the first retry, doubled for each further one.
+    pub initial_backoff: Duration,
+    /// Longest delay between attempts. A `Retry-After` asking for longer
+    /// ends the retries instead.
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            max_retries: DEFAULT_RETRIES,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that makes a single attempt.
+    pub fn never() -> Self {
+        RetryPolicy {
+            max_retries: 0,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// The delay before retry number `retry` (counting from 0): exponential
+    /// backoff capped at [`RetryPolicy::max_backoff`], of which a random part
+    /// of up to half is taken off so that clients failing together do not
+    /// retry together.
+    pub fn backoff(&self, retry: u32) -> Duration {
+        let delay = self
+            .initial_backoff
+            .saturating_mul(2u32.saturating_pow(retry))
+            .min(self.max_backoff);
+        let jitter = delay.mul_f64(random_fraction() / 2.0);
+        delay - jitter
+    }
+
+    /// How long to wait before retry number `retry` after `outcome`, or
+    /// `None` if the request should not be repeated.
+    ///
+    /// `outcome` is the HTTP status and `Retry-After` header of a response,
+    /// or the error that prevented one.
+    pub(crate) fn delay(
+        &self,
+        retry: u32,
+        outcome: Result<(u16, Option<&str>), &Error>,
+    ) -> Option<Duration> {
+        if retry >= self.max_retries {
+            return None;
+        }
+        match outcome {
+            Ok((status, retry_after)) if status == 429 || status >= 500 => {
+                match retry_after.and_then(parse_retry_after) {
+                    Some(wait) if wait > self.max_backoff => None,
+                    Some(wait) => Some(wait),
+                    None => Some(self.backoff(retry)),
+                }
+            }
+            Ok(_) => None,
+            Err(Error::Io(e)) if is_connection_error(e.kind()) => Some(self.backoff(retry)),
+            Err(Error::Timeout(_)) => Some(self.backoff(retry)),
+            Err(_) => None,
+        }
+    }
+}
+
+/// Whether an I/O error means the connection failed before Pushover could
+/// answer. Other errors, such as a malformed response to a message Pushover
+/// already accepted, are not retried so that nothing is delivered twice.
+fn is_connection_error(kind: io::ErrorKind) -> bool {
+    matches!(
+        kind,
+        io::ErrorKind::ConnectionRefused
+            | io::ErrorKind::ConnectionReset
+            | io::ErrorKind::ConnectionAborted
+            | io::ErrorKind::NotConnected
+            // Also failed name lookups, see Deadline::connect
+            | io::ErrorKind::HostUnreachable
+            | io::ErrorKind::NetworkUnreachable
+            // The client only reports this when no response byte arrived
+            | io::ErrorKind::UnexpectedEof
+    )
+}
+
+/// Reads a `Retry-After` given in seconds; HTTP dates are not supported.
+fn parse_retry_after(value: &str) -> Option<Duration> {
+    value.trim().parse().ok().map(Duration::from_secs)
+}
+
+// Good enough to spread retries out; not for anything that needs real randomness
+fn random_fraction() -> f64 {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.subsec_nanos())
+        .unwrap_or(0);
+    f64::from(nanos % 1000) / 1000.0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::timeout::resolve_error;
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy {
+            max_retries: 10,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(5),
+        };
+        for (retry, full) in [(0, 1), (1, 2), (2, 4), (3, 5), (9, 5)] {
+            let delay = policy.backoff(retry);
+            let full = Duration::from_secs(full);
+            assert!(
+                delay <= full && delay >= full / 2,
+                "retry {}: {:?}",
+                retry,
+                delay
+            );
+        }
+        // Does not overflow however many retries are allowed
+        assert!(policy.backoff(u32::MAX) <= Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_delay() {
+        let policy = RetryPolicy {
+            max_retries: 2,
+            initial_backoff: Duration::from_secs(1),
+            max_backoff: Duration::from_secs(30),
+        };
+        let timeout = Error::Timeout("Timed out".to_string());
+        let refused = Error::from(std::io::Error::from(std::io::ErrorKind::ConnectionRefused));
+        let tls = Error::Tls("TLS error: invalid certificate".to_string());
+
+        assert!(policy.delay(0, Err(&timeout)).is_some());
+        assert!(policy.delay(1, Err(&refused)).is_some());
+        assert!(policy.delay(2, Err(&refused)).is_none());
+        assert!(policy.delay(0, Err(&tls)).is_none());
+        // The request may have been accepted before the response went wrong
+        let malformed = Error::invalid_data("Malformed HTTP response: truncated chunk");
+        assert!(policy.delay(0, Err(&malformed)).is_none());
+        // One DNS blip should not lose the notification
+        let unresolved = resolve_error(
+            "api.pushover.net",
+            std::io::Error::other("failed to lookup address"),
+        );
+        assert!(policy.delay(0, Err(&unresolved)).is_some());
+
+        assert!(policy.delay(0, Ok((503, None))).is_some());
+        assert!(policy.delay(0, Ok((200, None))).is_none());
+        assert!(policy.delay(0, Ok((400, None))).is_none());
+        assert_eq!(
+            policy.delay(0, Ok((429, Some("7")))),
+            Some(Duration::from_secs(7))
+        );
+        // Waiting that long is not worth it
+        assert_eq!(policy.delay(0, Ok((429, Some("3600")))), None);
+
+        assert!(RetryPolicy::never().delay(0, Err(&timeout)).is_none());
+    }
+}
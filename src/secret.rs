@@ -0,0 +1,228 @@
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::process::{Command, Stdio};
+
+use crate::Error;
+
+/// The settings that can supply one credential, e.g. `token`, `token_file`,
+/// `token_env` and `token_command`. At most one of them may be set.
+pub(crate) struct SecretSources<'a> {
+    /// Name of the credential, used as the prefix of the setting names.
+    pub name: &'a str,
+    pub value: Option<&'a str>,
+    pub file: Option<&'a Path>,
+    pub env: Option<&'a str>,
+    pub command: Option<&'a [String]>,
+}
+
+impl SecretSources<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.count() == 0
+    }
+
+    fn count(&self) -> usize {
+        [
+            self.value.is_some(),
+            self.file.is_some(),
+            self.env.is_some(),
+            self.command.is_some(),
+        ]
+        .into_iter()
+        .filter(|&set| set)
+        .count()
+    }
+
+    /// Checks that no more than one source is set.
+    pub fn check(&self) -> Result<(), String> {
+        if self.count() > 1 {
+            return Err(format!(
+                "give only one of {0}, {0}_file, {0}_env and {0}_command",
+                self.name
+            ));
+        }
+        Ok(())
+    }
+
+    /// Reads the credential from whichever source is set, or returns `None`
+    /// if none is.
+    ///
+    /// Files and command output are trimmed of surrounding whitespace; only
+    /// the first line of command output is used, as with `pass`.
+    pub fn resolve(&self) -> Result<Option<String>, Error> {
+        self.check().map_err(Error::Config)?;
+
+        let (secret, source) = if let Some(value) = self.value {
+            return Ok(Some(value.to_string()));
+        } else if let Some(path) = self.file {
+            let content = fs::read_to_string(path).map_err(|e| {
+                Error::Config(format!(
+                    "{}_file: cannot read {}: {}",
+                    self.name,
+                    path.display(),
+                    e
+                ))
+            })?;
+            (
+                content.trim().to_string(),
+                format!("{}_file {}", self.name, path.display()),
+            )
+        } else if let Some(name) = self.env {
+            let value = env::var(name).map_err(|e| {
+                let problem = match e {
+                    env::VarError::NotPresent => "is not set",
+                    env::VarError::NotUnicode(_) => "is not valid UTF-8",
+                };
+                Error::Config(format!(
+                    "{}_env: environment variable {} {}",
+                    self.name, name, problem
+                ))
+            })?;
+            (
+                value.trim().to_string(),
+                format!("{}_env {}", self.name, name),
+            )
+        } else if let Some(command) = self.command {
+            (
+                run_command(self.name, command)?,
+                format!("{}_command", self.name),
+            )
+        } else {
+            return Ok(None);
+        };
+
+        if secret.is_empty() {
+            return Err(Error::Config(format!("{}: no {} found", source, self.name)));
+        }
+        Ok(Some(secret))
+    }
+}
+
+fn run_command(name: &str, command: &[String]) -> Result<String, Error> {
+    let (program, args) = command
+        .split_first()
+        .ok_or_else(|| Error::Config(format!("{}_command is empty", name)))?;
+
+    // stderr is passed through so password managers can prompt
+    let output = Command::new(program)
+        .args(args)
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::inherit())
+        .output()
+        .map_err(|e| {
+            Error::Config(format!(
+                "{}_command: cannot run {}: {}",
+                name,
+                command.join(" "),
+                e
+            ))
+        })?;
+    if !output.status.success() {
+        return Err(Error::Config(format!(
+            "{}_command: {} failed ({})",
+            name,
+            command.join(" "),
+            output.status
+        )));
+    }
+
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().next().unwrap_or_default().trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(name: &str) -> SecretSources<'_> {
+        SecretSources {
+            name,
+            value: None,
+            file: None,
+            env: None,
+            command: None,
+        }
+    }
+
+    #[test]
+    fn test_resolve_sources() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("token");
+        fs::write(&path, "file_token\n").unwrap();
+        let secret = SecretSources {
+            file: Some(&path),
+            ..sources("token")
+        };
+        assert_eq!(secret.resolve().unwrap(), Some("file_token".to_string()));
+
+        let secret = SecretSources {
+            env: Some("PATH"),
+            ..sources("token")
+        };
+        assert_eq!(secret.resolve().unwrap(), Some(env::var("PATH").unwrap()));
+
+        let command = vec!["echo".to_string(), "command_token".to_string()];
+        let secret = SecretSources {
+            command: Some(&command),
+            ..sources("token")
+        };
+        assert_eq!(secret.resolve().unwrap(), Some("command_token".to_string()));
+
+        assert_eq!(sources("token").resolve().unwrap(), None);
+    }
+
+    #[test]
+    fn test_resolve_errors_name_the_source() {
+        let secret = SecretSources {
+            value: Some("literal"),
+            env: Some("PUSHOVER_TOKEN"),
+            ..sources("token")
+        };
+        assert_eq!(
+            secret.resolve().unwrap_err().to_string(),
+            "give only one of token, token_file, token_env and token_command"
+        );
+
+        let secret = SecretSources {
+            env: Some("PUSHOVER_TEST_UNSET_VARIABLE"),
+            ..sources("user")
+        };
+        let err = secret.resolve().unwrap_err();
+        assert!(matches!(err, Error::Config(_)));
+        assert_eq!(
+            err.to_string(),
+            "user_env: environment variable PUSHOVER_TEST_UNSET_VARIABLE is not set"
+        );
+
+        let secret = SecretSources {
+            file: Some(Path::new("/nonexistent/token")),
+            ..sources("token")
+        };
+        assert!(secret
+            .resolve()
+            .unwrap_err()
+            .to_string()
+            .starts_with("token_file: cannot read /nonexistent/token"));
+
+        let command = vec!["false".to_string()];
+        let secret = SecretSources {
+            command: Some(&command),
+            ..sources("token")
+        };
+        assert!(secret
+            .resolve()
+            .unwrap_err()
+            .to_string()
+            .starts_with("token_command: false failed"));
+
+        let command = vec!["true".to_string()];
+        let secret = SecretSources {
+            command: Some(&command),
+            ..sources("token")
+        };
+        assert_eq!(
+            secret.resolve().unwrap_err().to_string(),
+            "token_command: no token found"
+        );
+    }
+}
@@ -0,0 +1,124 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::cache::{cache_dir, cached};
+use crate::{Client, Error};
+
+/// Sounds every Pushover user has, with their descriptions.
+pub const BUILTIN_SOUNDS: &[(&str, &str)] = &[
+    ("pushover", "Pushover (default)"),
+    ("bike", "Bike"),
+    ("bugle", "Bugle"),
+    ("cashregister", "Cash Register"),
+    ("classical", "Classical"),
+    ("cosmic", "Cosmic"),
+    ("falling", "Falling"),
+    ("gamelan", "Gamelan"),
+    ("incoming", "Incoming"),
+    ("intermission", "Intermission"),
+    ("magic", "Magic"),
+    ("mechanical", "Mechanical"),
+    ("pianobar", "Piano Bar"),
+    ("siren", "Siren"),
+    ("spacealarm", "Space Alarm"),
+    ("tugboat", "Tug Boat"),
+    ("alien", "Alien Alarm (long)"),
+    ("climb", "Climb (long)"),
+    ("persistent", "Persistent (long)"),
+    ("echo", "Pushover Echo (long)"),
+    ("updown", "Up Down (long)"),
+    ("vibrate", "Vibrate Only"),
+    ("none", "None (silent)"),
+];
+
+/// How long [`Client::sounds_cached`] reuses a fetched sound list.
+pub const SOUNDS_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+#[derive(Deserialize)]
+struct SoundsResponse {
+    sounds: BTreeMap<String, String>,
+}
+
+/// The file [`Client::sounds_cached`] results are kept in by the CLI:
+/// `$XDG_CACHE_HOME/pushover/sounds.json`, else `~/.cache/pushover/sounds.json`.
+pub fn sounds_cache_path() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("sounds.json"))
+}
+
+impl Client {
+    /// Fetches the sounds available to the application, by name with their
+    /// descriptions, including custom sounds uploaded to the account.
+    pub fn sounds(&self) -> Result<BTreeMap<String, String>, Error> {
+        let response: SoundsResponse = self.get("sounds.json")?;
+        Ok(response.sounds)
+    }
+
+    /// Like [`Client::sounds`], but reuses a list for the same API URL and
+    /// token found in `cache_file` if it is younger than [`SOUNDS_CACHE_TTL`].
+    pub fn sounds_cached(&self, cache_file: &Path) -> Result<BTreeMap<String, String>, Error> {
+        cached(
+            cache_file,
+            &[self.api_url(), self.token()],
+            SOUNDS_CACHE_TTL,
+            || self.sounds(),
+        )
+    }
+}
+
+/// Returns the sound in `sounds` closest to the misspelled `name`, if any is
+/// within a few typos of it.
+pub fn closest_sound<'a>(name: &str, sounds: impl IntoIterator<Item = &'a str>) -> Option<&'a str> {
+    let name = name.to_lowercase();
+    let max_distance = (name.chars().count() / 3).clamp(2, 3);
+    sounds
+        .into_iter()
+        .map(|sound| (edit_distance(&name, sound), sound))
+        .filter(|(distance, _)| *distance <= max_distance)
+        .min_by_key(|(distance, _)| *distance)
+        .map(|(_, sound)| sound)
+}
+
+/// Levenshtein distance: the fewest insertions, deletions and substitutions
+/// turning `a` into `b`.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    for (i, a_char) in a.chars().enumerate() {
+        let mut current = vec![i + 1];
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != *b_char);
+            current.push(substitution.min(previous[j + 1] + 1).min(current[j] + 1));
+        }
+        previous = current;
+    }
+    previous[b.len()]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_edit_distance() {
+        assert_eq!(edit_distance("siren", "siren"), 0);
+        assert_eq!(edit_distance("sirne", "siren"), 2);
+        assert_eq!(edit_distance("bugel", "bugle"), 2);
+        assert_eq!(edit_distance("", "none"), 4);
+        assert_eq!(edit_distance("kitten", "sitting"), 3);
+    }
+
+    #[test]
+    fn test_closest_sound() {
+        let builtin = || BUILTIN_SOUNDS.iter().map(|(name, _)| *name);
+        assert_eq!(closest_sound("sirne", builtin()), Some("siren"));
+        assert_eq!(
+            closest_sound("Cashregistr", builtin()),
+            Some("cashregister")
+        );
+        assert_eq!(closest_sound("pushvoer", builtin()), Some("pushover"));
+        assert_eq!(closest_sound("trumpet", builtin()), None);
+    }
+}
@@ -0,0 +1,164 @@
+use std::io;
+use std::net::{TcpStream, ToSocketAddrs};
+use std::time::{Duration, Instant};
+
+use crate::Error;
+
+/// Default limit for establishing the TCP connection.
+pub const DEFAULT_CONNECT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default limit for each read from or write to the server.
+pub const DEFAULT_READ_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Default limit for a whole request, from connecting to the end of the
+/// response.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// How long a [`Client`](crate::Client) waits for the network before giving
+/// up with [`Error::Timeout`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Timeouts {
+    /// Limit for establishing the TCP connection, to the proxy if one is used.
+    pub connect: Duration,
+    /// Limit for each read or write, i.e. how long the server may stay silent.
+    pub read: Duration,
+    /// Limit for the whole request, however the time is spent, including
+    /// any retries and the waits between them.
+    pub overall: Duration,
+}
+
+impl Default for Timeouts {
+    fn default() -> Self {
+        Timeouts {
+            connect: DEFAULT_CONNECT_TIMEOUT,
+            read: DEFAULT_READ_TIMEOUT,
+            overall: DEFAULT_TIMEOUT,
+        }
+    }
+}
+
+/// The timeouts of one request and its retries, counted from when it started.
+pub(crate) struct Deadline {
+    timeouts: Timeouts,
+    started: Instant,
+}
+
+impl Deadline {
+    pub fn start(timeouts: Timeouts) -> Self {
+        Deadline {
+            timeouts,
+            started: Instant::now(),
+        }
+    }
+
+    /// Connects to `host:port`, trying each of its addresses in turn within
+    /// the connect timeout. Name resolution itself cannot be limited.
+    pub fn connect(&self, host: &str, port: u16) -> Result<TcpStream, Error> {
+        let what = format!("connecting to {}:{}", host, port);
+        let addresses = (host, port)
+            .to_socket_addrs()
+            .map_err(|e| resolve_error(host, e))?;
+
+        let mut last_error = None;
+        for address in addresses {
+            let limit = self.timeouts.connect.min(self.remaining(&what)?);
+            match TcpStream::connect_timeout(&address, limit) {
+                Ok(stream) => return Ok(stream),
+                Err(e) => last_error = Some(e),
+            }
+        }
+        Err(match last_error {
+            Some(e) => self.io_error(e, &what),
+            None => resolve_error(host, io::Error::other("no addresses found")),
+        })
+    }
+
+    /// Limits the next reads and writes on `stream` to the read timeout, or
+    /// to what is left of the overall timeout if that is sooner.
+    pub fn arm(&self, stream: &TcpStream, what: &str) -> Result<(), Error> {
+        let limit = self.timeouts.read.min(self.remaining(what)?);
+        stream.set_read_timeout(Some(limit))?;
+        stream.set_write_timeout(Some(limit))?;
+        Ok(())
+    }
+
+    /// Turns a timed-out socket operation into [`Error::Timeout`]; other
+    /// errors are converted as usual.
+    pub fn io_error(&self, error: io::Error, what: &str) -> Error {
+        match error.kind() {
+            // Socket timeouts show up as WouldBlock on Unix
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => self.timeout(what),
+            _ => Error::from(error),
+        }
+    }
+
+    /// The time left of the overall timeout, or [`Error::Timeout`] if none is.
+    pub fn remaining(&self, what: &str) -> Result<Duration, Error> {
+        match self.timeouts.overall.checked_sub(self.started.elapsed()) {
+            Some(remaining) if !remaining.is_zero() => Ok(remaining),
+            _ => Err(self.timeout(what)),
+        }
+    }
+
+    fn timeout(&self, what: &str) -> Error {
+        Error::Timeout(format!(
+            "Timed out {} after {:.1}s",
+            what,
+            self.started.elapsed().as_secs_f64()
+        ))
+    }
+}
+
+/// A failed name lookup for `host`. The standard library does not categorise
+/// these, so it is reported as [`io::ErrorKind::HostUnreachable`] to be
+/// retried like other connection failures, as it is often a passing DNS
+/// problem.
+pub(crate) fn resolve_error(host: &str, error: io::Error) -> Error {
+    Error::Io(io::Error::new(
+        io::ErrorKind::HostUnreachable,
+        format!("Cannot resolve {}: {}", host, error),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_deadline_expired() {
+        let deadline = Deadline::start(Timeouts {
+            overall: Duration::ZERO,
+            ..Timeouts::default()
+        });
+        let err = deadline.connect("127.0.0.1", 443).unwrap_err();
+        assert!(matches!(err, Error::Timeout(_)));
+        assert!(err
+            .to_string()
+            .starts_with("Timed out connecting to 127.0.0.1:443 after"));
+
+        let refused = io::Error::new(io::ErrorKind::ConnectionRefused, "refused");
+        assert!(matches!(
+            deadline.io_error(refused, "reading"),
+            Error::Io(_)
+        ));
+        let timed_out = io::Error::from(io::ErrorKind::WouldBlock);
+        assert!(matches!(
+            deadline.io_error(timed_out, "reading"),
+            Error::Timeout(_)
+        ));
+    }
+
+    #[test]
+    fn test_resolve_failure() {
+        let deadline = Deadline::start(Timeouts::default());
+        match deadline.connect("unresolvable.invalid", 443) {
+            Err(Error::Io(e)) => {
+                assert_eq!(e.kind(), io::ErrorKind::HostUnreachable);
+                assert!(e
+                    .to_string()
+                    .starts_with("Cannot resolve unresolvable.invalid: "));
+            }
+            other => panic!("expected a resolve error, got {:?}", other),
+        }
+    }
+}
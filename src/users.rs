@@ -0,0 +1,104 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::cache::{cache_dir, cached};
+use crate::message::split_devices;
+use crate::receipts::int_bool;
+use crate::{Client, Error};
+
+/// How long [`Client::validate_user_cached`] trusts an earlier successful check.
+pub const VALIDATION_CACHE_TTL: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// What Pushover knows about a user or group key.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct UserValidation {
+    /// Whether the key belongs to a delivery group rather than a user.
+    #[serde(default, deserialize_with = "int_bool")]
+    pub group: bool,
+    /// Names of the user's active devices.
+    #[serde(default)]
+    pub devices: Vec<String>,
+    /// Platforms the user has a license for, e.g. `iOS` or `Android`.
+    #[serde(default)]
+    pub licenses: Vec<String>,
+}
+
+/// The file [`Client::validate_user_cached`] results are kept in by the CLI:
+/// `$XDG_CACHE_HOME/pushover/validation.json`, else
+/// `~/.cache/pushover/validation.json`.
+pub fn validation_cache_path() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("validation.json"))
+}
+
+impl Client {
+    /// Checks that `user` is a valid user or group key and, if given, that
+    /// `device` is one of its devices. Several devices can be given
+    /// separated by commas; each is checked in turn.
+    ///
+    /// An unknown key or device is reported as [`Error::Api`].
+    pub fn validate_user(&self, user: &str, device: Option<&str>) -> Result<UserValidation, Error> {
+        let Some(devices) = device else {
+            return self.post("users/validate.json", vec![("user", user.to_string())]);
+        };
+        let mut validation = None;
+        for device in split_devices(devices) {
+            validation = Some(self.post(
+                "users/validate.json",
+                vec![("user", user.to_string()), ("device", device)],
+            )?);
+        }
+        // split always yields at least one name
+        Ok(validation.expect("no device names"))
+    }
+
+    /// Like [`Client::validate_user`], but reuses a successful result for the
+    /// same API URL, user and device found in `cache_file` if it is younger
+    /// than [`VALIDATION_CACHE_TTL`].
+    ///
+    /// Failures are never cached. The cache holds a hash of the key rather
+    /// than the key itself; if it cannot be read or written it is ignored.
+    pub fn validate_user_cached(
+        &self,
+        user: &str,
+        device: Option<&str>,
+        cache_file: &Path,
+    ) -> Result<UserValidation, Error> {
+        let (group, devices, licenses) = cached(
+            cache_file,
+            &[self.api_url(), user, device.unwrap_or_default()],
+            VALIDATION_CACHE_TTL,
+            || {
+                let validation = self.validate_user(user, device)?;
+                Ok((validation.group, validation.devices, validation.licenses))
+            },
+        )?;
+        Ok(UserValidation {
+            group,
+            devices,
+            licenses,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_user_validation_deserialize() {
+        let body = r#"{
+            "status": 1,
+            "group": 0,
+            "devices": ["iphone", "nexus"],
+            "licenses": ["Android", "iOS"],
+            "request": "5042853c-402d-4a18-abcb-168734a801de"
+        }"#;
+
+        let validation: UserValidation = serde_json::from_str(body).unwrap();
+        assert!(!validation.group);
+        assert_eq!(validation.devices, vec!["iphone", "nexus"]);
+        assert_eq!(validation.licenses, vec!["Android", "iOS"]);
+    }
+}
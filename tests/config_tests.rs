@@ -276,3 +276,205 @@ device = "José's iPhone"
     assert_eq!(notification.sound, Some("pushover".to_string()));
     assert_eq!(notification.device, Some("José's iPhone".to_string()));
 }
+
+#[test]
+fn test_config_emergency_defaults() {
+    let config_content = r#"
+[pushover]
+user = "uQiRzpo4DXghDmr9QzzfQu27cmVRsG"
+token = "azGDORePK8gMaC0QOYAMyEEuzJnyUi"
+
+[notification]
+retry = 60
+expire = 3600
+callback = "https://example.com/ack"
+"#;
+
+    let config: Config = toml::from_str(config_content).unwrap();
+
+    let notification = config.notification.unwrap();
+    assert_eq!(notification.retry, Some(60));
+    assert_eq!(notification.expire, Some(3600));
+    assert_eq!(
+        notification.callback,
+        Some("https://example.com/ack".to_string())
+    );
+    assert!(notification.sound.is_none());
+}
+
+#[test]
+fn test_config_profiles() {
+    let config_content = r#"
+[pushover]
+user = "uQiRzpo4DXghDmr9QzzfQu27cmVRsG"
+token = "azGDORePK8gMaC0QOYAMyEEuzJnyUi"
+
+[notification]
+priority = 0
+
+[profiles.deploys]
+token = "aDeployToken0000000000000000000"
+default_title = "Deploy"
+
+[profiles.oncall]
+user = "gOnCallGroupKey000000000000000"
+device = "pager"
+priority = 1
+"#;
+
+    let config: Config = toml::from_str(config_content).unwrap();
+    assert_eq!(config.profiles.len(), 2);
+    assert_eq!(config.notification.as_ref().unwrap().priority, Some(0));
+
+    let deploys = &config.profiles["deploys"];
+    assert_eq!(
+        deploys.token,
+        Some("aDeployToken0000000000000000000".to_string())
+    );
+    assert_eq!(deploys.default_title, Some("Deploy".to_string()));
+    assert!(deploys.user.is_none());
+
+    let oncall = config.with_profile("oncall").unwrap();
+    assert_eq!(oncall.pushover.user, "gOnCallGroupKey000000000000000");
+    assert_eq!(oncall.pushover.token, "azGDORePK8gMaC0QOYAMyEEuzJnyUi");
+    let notification = oncall.notification.unwrap();
+    assert_eq!(notification.device, Some("pager".to_string()));
+    assert_eq!(notification.priority, Some(1));
+}
+
+#[test]
+fn test_config_secret_sources() {
+    let config_content = r#"
+[pushover]
+user_file = "/run/secrets/pushover-user"
+token_command = ["pass", "show", "pushover/token"]
+"#;
+
+    let config: Config = toml::from_str(config_content).unwrap();
+    assert_eq!(
+        config.pushover.user_file,
+        Some("/run/secrets/pushover-user".into())
+    );
+    assert_eq!(
+        config.pushover.token_command,
+        Some(vec![
+            "pass".to_string(),
+            "show".to_string(),
+            "pushover/token".to_string()
+        ])
+    );
+    // Resolved by load_config, not while parsing
+    assert_eq!(config.pushover.user, "");
+    assert_eq!(config.pushover.token, "");
+
+    let config_content = r#"
+[pushover]
+user = "uQiRzpo4DXghDmr9QzzfQu27cmVRsG"
+token = "azGDORePK8gMaC0QOYAMyEEuzJnyUi"
+token_env = "PUSHOVER_TOKEN"
+"#;
+    let err = toml::from_str::<Config>(config_content).unwrap_err();
+    assert!(err
+        .to_string()
+        .contains("give only one of token, token_file, token_env and token_command"));
+
+    let config_content = r#"
+[pushover]
+user_env = "PUSHOVER_USER"
+"#;
+    let err = toml::from_str::<Config>(config_content).unwrap_err();
+    assert!(err.to_string().contains("missing field `token`"));
+}
+
+#[test]
+fn test_config_validate() {
+    let config: Config = toml::from_str(
+        r#"
+[pushover]
+user = "uQiRzpo4DXghDmr9QzzfQu27cmVRsG"
+token = "azGDORePK8gMaC0QOYAMyEEuzJnyUi"
+"#,
+    )
+    .unwrap();
+    assert!(config.validate().is_ok());
+
+    let config: Config = toml::from_str(
+        r#"
+[pushover]
+user = "__YOUR_USER_KEY__"
+token = "azGDORePK8gMaC0QOYAMyEEuzJnyU"
+
+[profiles.backups]
+token = "azGDORePK8gMaC0QOYAMyEEuzJny-i"
+"#,
+    )
+    .unwrap();
+    assert_eq!(
+        config.validate().unwrap_err().to_string(),
+        "user is still the placeholder __YOUR_USER_KEY__; \
+         token must be 30 characters long, not 29; \
+         profiles.backups.token may only contain letters and digits"
+    );
+}
+
+#[test]
+fn test_config_redacted() {
+    let config: Config = toml::from_str(
+        r#"
+[pushover]
+user = "uQiRzpo4DXghDmr9QzzfQu27cmVRsG"
+token = "__YOUR_APP_TOKEN__"
+default_title = "Server"
+
+[profiles.backups]
+token = "azGDORePK8gMaC0QOYAMyEEuzJnyUi"
+"#,
+    )
+    .unwrap();
+
+    let redacted = config.redacted();
+    assert_eq!(redacted.pushover.user, "uQiR…");
+    assert_eq!(redacted.pushover.token, "__YOUR_APP_TOKEN__");
+    assert_eq!(redacted.pushover.default_title, Some("Server".to_string()));
+    assert_eq!(
+        redacted.profiles["backups"].token,
+        Some("azGD…".to_string())
+    );
+    assert_eq!(config.pushover.user, "uQiRzpo4DXghDmr9QzzfQu27cmVRsG");
+}
+
+#[test]
+fn test_config_device_list() {
+    let config: Config = toml::from_str(
+        r#"
+[pushover]
+user = "uQiRzpo4DXghDmr9QzzfQu27cmVRsG"
+token = "azGDORePK8gMaC0QOYAMyEEuzJnyUi"
+
+[notification]
+device = ["iphone", "desktop"]
+
+[profiles.pager]
+device = "pager"
+"#,
+    )
+    .unwrap();
+
+    assert_eq!(
+        config.notification.unwrap().device,
+        Some("iphone,desktop".to_string())
+    );
+    assert_eq!(config.profiles["pager"].device, Some("pager".to_string()));
+
+    let result = toml::from_str::<Config>(
+        r#"
+[pushover]
+user = "uQiRzpo4DXghDmr9QzzfQu27cmVRsG"
+token = "azGDORePK8gMaC0QOYAMyEEuzJnyUi"
+
+[notification]
+device = 1
+"#,
+    );
+    assert!(result.is_err());
+}
@@ -1,7 +1,8 @@
 use std::env;
 use std::fs;
-use std::path::PathBuf;
-use std::process::Command;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 use tempfile::TempDir;
 
 // Helper function to get the path to our binary
@@ -159,6 +160,589 @@ fn test_missing_argument_for_flag() {
     );
 }
 
+#[test]
+fn test_emergency_retry_and_expire_limits() {
+    let output = Command::new(get_binary_path())
+        .args(["-m", "Test Message", "-p", "2", "--retry", "10"])
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Retry must be at least 30 seconds"));
+
+    let output = Command::new(get_binary_path())
+        .args(["-m", "Test Message", "-p", "2", "--expire", "10801"])
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Expire must be at most 10800 seconds"));
+}
+
+#[test]
+fn test_emergency_priority_requires_retry_and_expire() {
+    let output = Command::new(get_binary_path())
+        .args(["-m", "Test Message", "-p", "2"])
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("requires both retry and expire"));
+}
+
+#[test]
+fn test_wait_ack_requires_emergency_priority() {
+    let output = Command::new(get_binary_path())
+        .args(["-m", "Test Message", "--wait-ack"])
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("--wait-ack requires emergency priority"));
+}
+
+#[test]
+fn test_receipt_subcommand_requires_receipt() {
+    let output = Command::new(get_binary_path())
+        .arg("receipt")
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Receipt is required"));
+}
+
+#[test]
+fn test_cancel_subcommand_argument_validation() {
+    // Neither a receipt nor a tag
+    let output = Command::new(get_binary_path())
+        .arg("cancel")
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Give either a receipt or --tag <tag>"));
+
+    // Both a receipt and a tag
+    let output = Command::new(get_binary_path())
+        .args(["cancel", "r1234", "--tag", "db"])
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Give either a receipt or --tag <tag>"));
+
+    let output = Command::new(get_binary_path())
+        .args(["cancel", "--tag"])
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Option --tag requires an argument"));
+}
+
+#[test]
+fn test_attach_rejects_missing_and_oversized_files() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let output = Command::new(get_binary_path())
+        .args(["-m", "Test Message", "--attach"])
+        .arg(temp_dir.path().join("missing.png"))
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Error reading attachment"));
+
+    let big_image = temp_dir.path().join("big.png");
+    let file = fs::File::create(&big_image).unwrap();
+    file.set_len(5 * 1024 * 1024 + 1).unwrap();
+
+    let output = Command::new(get_binary_path())
+        .args(["-m", "Test Message", "--attach"])
+        .arg(&big_image)
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("the maximum is 5242880"));
+
+    let text_file = temp_dir.path().join("notes.txt");
+    fs::write(&text_file, "not an image").unwrap();
+
+    let output = Command::new(get_binary_path())
+        .args(["-m", "Test Message", "--attach"])
+        .arg(&text_file)
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Cannot determine the image type"));
+}
+
+#[test]
+fn test_message_from_stdin() {
+    let mut child = Command::new(get_binary_path())
+        .args(["-t", "Piped"])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Failed to execute binary");
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(b"output from a cron job\n")
+        .unwrap();
+    let output = child.wait_with_output().unwrap();
+
+    // The message is accepted and the run proceeds to the network stage
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!stderr.contains("Message is required"));
+    assert!(!stderr.contains("Usage:"));
+
+    // `-m -` reads standard input explicitly; empty input is still rejected
+    let output = Command::new(get_binary_path())
+        .args(["-m", "-"])
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(!output.status.success());
+    assert!(stderr.contains("Message is required"));
+}
+
+#[test]
+fn test_message_file() {
+    let temp_dir = TempDir::new().unwrap();
+
+    let output = Command::new(get_binary_path())
+        .arg("--message-file")
+        .arg(temp_dir.path().join("missing.txt"))
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(stderr.contains("Error reading message"));
+
+    let message_path = temp_dir.path().join("body.txt");
+    fs::write(&message_path, "a".repeat(5000)).unwrap();
+
+    // A usage error, reported before the configuration is loaded
+    let output = Command::new(get_binary_path())
+        .args(["--config", "/nonexistent/config.toml"])
+        .args(["-m", "inline", "--message-file"])
+        .arg(&message_path)
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.contains("Use either -m or --message-file, not both."));
+    assert!(stderr.contains("Usage:"));
+
+    // Oversized bodies are truncated rather than rejected
+    let output = Command::new(get_binary_path())
+        .args(["-v", "--message-file"])
+        .arg(&message_path)
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert!(stderr.contains("Message truncated to the 1024 character limit"));
+    assert!(!stderr.contains("characters long, the maximum is"));
+}
+
+#[test]
+fn test_api_url_and_ca_file_from_environment() {
+    let output = Command::new(get_binary_path())
+        .args(["-m", "Test Message"])
+        .env("PUSHOVER_API_URL", "http://localhost:8080/1")
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr.contains("Only HTTPS URLs are supported"));
+
+    let output = Command::new(get_binary_path())
+        .args(["-m", "Test Message"])
+        .env("PUSHOVER_CA_FILE", "/nonexistent/ca.pem")
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(stderr.contains("Cannot read CA file /nonexistent/ca.pem"));
+}
+
+#[test]
+fn test_config_option_and_environment_variable() {
+    let output = Command::new(get_binary_path())
+        .args(["--config", "/nonexistent/config.toml", "-m", "Test Message"])
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr.contains("Cannot read config file /nonexistent/config.toml"));
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("broken.toml");
+    fs::write(&config_path, "[pushover\nuser = ").unwrap();
+
+    let output = Command::new(get_binary_path())
+        .args(["-m", "Test Message"])
+        .env("PUSHOVER_CONFIG", &config_path)
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr.contains("Invalid TOML in config file"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_config_file_permissions() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = create_test_config(&temp_dir);
+    let config_arg = config_path.to_str().unwrap();
+    fs::set_permissions(&config_path, fs::Permissions::from_mode(0o644)).unwrap();
+
+    let output = Command::new(get_binary_path())
+        .args(["config", "check", "--config", config_arg])
+        .output()
+        .expect("Failed to execute binary");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stdout.contains("holds credentials but is readable by its group and others (mode 644)"));
+    assert!(stdout.contains(&format!("fix with: chmod go-r {}", config_arg)));
+
+    // Loading only warns by default
+    let output = Command::new(get_binary_path())
+        .args(["--config", config_arg, "-m", "Test Message"])
+        .env("PUSHOVER_API_URL", "https://127.0.0.1:1/1")
+        .output()
+        .expect("Failed to execute binary");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(5));
+    assert!(stderr.contains("Warning: "));
+    assert!(stderr.contains("fix with: chmod go-r"));
+
+    let strict_path = temp_dir.path().join("strict.toml");
+    fs::write(
+        &strict_path,
+        "[pushover]\nuser = \"test_user_key_12345\"\ntoken_command = [\"false\"]\nstrict_permissions = true\n",
+    )
+    .unwrap();
+    fs::set_permissions(&strict_path, fs::Permissions::from_mode(0o666)).unwrap();
+    let output = Command::new(get_binary_path())
+        .args([
+            "--config",
+            strict_path.to_str().unwrap(),
+            "-m",
+            "Test Message",
+        ])
+        .output()
+        .expect("Failed to execute binary");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr.contains("Refusing to load configuration because strict_permissions is set"));
+    assert!(stderr.contains("is writable by its group and others (mode 666)"));
+    // The token command is not run from a file others can change
+    assert!(!stderr.contains("token_command"));
+
+    fs::set_permissions(&config_path, fs::Permissions::from_mode(0o600)).unwrap();
+    let output = Command::new(get_binary_path())
+        .args(["config", "check", "--config", config_arg])
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains(": ok"));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_token_command_not_run_from_writable_file_without_strict_mode() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let marker = temp_dir.path().join("ran");
+    let config_path = temp_dir.path().join("config.toml");
+    fs::write(
+        &config_path,
+        format!(
+            "[pushover]\nuser = \"uQiRzpo4DXghDmr9QzzfQu27cmVRsG\"\ntoken_command = [\"sh\", \"-c\", \"touch {}; echo azGDORePK8gMaC0QOYAMyEEuzJnyUi\"]\n",
+            marker.display()
+        ),
+    )
+    .unwrap();
+    let show_config = || {
+        Command::new(get_binary_path())
+            .args(["config", "show", "--config"])
+            .arg(&config_path)
+            .output()
+            .expect("Failed to execute binary")
+    };
+
+    fs::set_permissions(&config_path, fs::Permissions::from_mode(0o666)).unwrap();
+    let output = show_config();
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(3), "{}", stderr);
+    assert!(stderr.contains("Refusing to use token_command"));
+    assert!(stderr.contains("is writable by its group and others (mode 666)"));
+    assert!(!stderr.contains("strict_permissions"));
+    assert!(!marker.exists());
+
+    fs::set_permissions(&config_path, fs::Permissions::from_mode(0o600)).unwrap();
+    let output = show_config();
+    assert!(output.status.success());
+    assert!(String::from_utf8_lossy(&output.stdout).contains("token = \"azGD…\""));
+    assert!(marker.exists());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_secret_sources_refused_from_untrusted_files() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let temp_dir = TempDir::new().unwrap();
+    let marker = temp_dir.path().join("ran");
+    let config_content = format!(
+        "[pushover]\nuser = \"uQiRzpo4DXghDmr9QzzfQu27cmVRsG\"\ntoken_command = [\"touch\", \"{}\"]\n",
+        marker.display()
+    );
+    let run = |config: Option<&Path>| {
+        let mut command = Command::new(get_binary_path());
+        command
+            .args(["-m", "Test Message"])
+            .current_dir(temp_dir.path())
+            .env_remove("PUSHOVER_CONFIG")
+            .env("XDG_CONFIG_HOME", temp_dir.path().join("xdg"))
+            .env("HOME", temp_dir.path());
+        if let Some(config) = config {
+            command.arg("--config").arg(config);
+        }
+        command.output().expect("Failed to execute binary")
+    };
+
+    // Not from the development fallback in the working directory, which is
+    // only read without a system file
+    if !Path::new(pushover::SYSTEM_CONFIG_PATH).exists() {
+        let local_dir = temp_dir.path().join("etc/pushover");
+        fs::create_dir_all(&local_dir).unwrap();
+        let local_path = local_dir.join("config.toml");
+        fs::write(&local_path, &config_content).unwrap();
+        fs::set_permissions(&local_path, fs::Permissions::from_mode(0o600)).unwrap();
+
+        let output = run(None);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(3), "{}", stderr);
+        assert!(stderr.contains(
+            "Refusing to use token_command from etc/pushover/config.toml: only the system and user config files may read files or run commands"
+        ));
+        assert!(!marker.exists());
+    }
+
+    // Not from a file its group can change, even outside strict mode
+    let config_path = temp_dir.path().join("shared.toml");
+    fs::write(&config_path, &config_content).unwrap();
+    fs::set_permissions(&config_path, fs::Permissions::from_mode(0o620)).unwrap();
+    let output = run(Some(&config_path));
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(3), "{}", stderr);
+    assert!(stderr.contains(&format!(
+        "Refusing to use token_command from {}: it is writable by its group (mode 620)",
+        config_path.display()
+    )));
+    assert!(!marker.exists());
+
+    // Not from a file owned by another user, which only root can set up
+    // SAFETY: geteuid has no preconditions and cannot fail
+    if unsafe { libc::geteuid() } == 0 {
+        fs::set_permissions(&config_path, fs::Permissions::from_mode(0o600)).unwrap();
+        std::os::unix::fs::chown(&config_path, Some(1), None).unwrap();
+        let output = run(Some(&config_path));
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert_eq!(output.status.code(), Some(3), "{}", stderr);
+        assert!(stderr.contains("it is owned by another user (uid 1)"));
+        assert!(!marker.exists());
+    }
+}
+
+#[test]
+fn test_working_directory_config_not_layered_under_user_file() {
+    let temp_dir = TempDir::new().unwrap();
+    let local_dir = temp_dir.path().join("etc/pushover");
+    fs::create_dir_all(&local_dir).unwrap();
+    fs::write(
+        local_dir.join("config.toml"),
+        "[pushover]\napi_url = \"https://attacker.invalid/1\"\n",
+    )
+    .unwrap();
+    let user_dir = temp_dir.path().join("xdg/pushover");
+    fs::create_dir_all(&user_dir).unwrap();
+    fs::write(
+        user_dir.join("config.toml"),
+        "[pushover]\nuser = \"uQiRzpo4DXghDmr9QzzfQu27cmVRsG\"\ntoken = \"azGDORePK8gMaC0QOYAMyEEuzJnyUi\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .args(["config", "show"])
+        .current_dir(temp_dir.path())
+        .env_remove("PUSHOVER_CONFIG")
+        .env("XDG_CONFIG_HOME", temp_dir.path().join("xdg"))
+        .env("HOME", temp_dir.path())
+        .output()
+        .expect("Failed to execute binary");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(stdout.contains("token = \"azGD…\""));
+    assert!(!stdout.contains("attacker.invalid"));
+
+    let output = Command::new(get_binary_path())
+        .args(["config", "path"])
+        .current_dir(temp_dir.path())
+        .env_remove("PUSHOVER_CONFIG")
+        .env("XDG_CONFIG_HOME", temp_dir.path().join("xdg"))
+        .env("HOME", temp_dir.path())
+        .output()
+        .expect("Failed to execute binary");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("etc/pushover/config.toml (not read)"));
+}
+
+#[test]
+fn test_config_init_show_and_path() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = temp_dir.path().join("pushover/config.toml");
+    let config_arg = config_path.to_str().unwrap();
+
+    let output = Command::new(get_binary_path())
+        .args(["config", "init", "--config", config_arg])
+        .args(["--user", "uQiRzpo4DXghDmr9QzzfQu27cmVRsG"])
+        .args(["--token", "azGDORePK8gMaC0QOYAMyEEuzJnyUi"])
+        .args(["--title", "Backups", "--device", "iphone"])
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to execute binary");
+    assert!(output.status.success());
+    let config: pushover::Config =
+        toml::from_str(&fs::read_to_string(&config_path).unwrap()).unwrap();
+    assert_eq!(config.pushover.token, "azGDORePK8gMaC0QOYAMyEEuzJnyUi");
+    assert_eq!(config.pushover.default_title, Some("Backups".to_string()));
+    assert_eq!(
+        config.notification.unwrap().device,
+        Some("iphone".to_string())
+    );
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&config_path).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+    }
+
+    // An existing file is only replaced with --force
+    let output = Command::new(get_binary_path())
+        .args(["config", "init", "--config", config_arg])
+        .args(["--user", "uQiRzpo4DXghDmr9QzzfQu27cmVRsG"])
+        .args(["--token", "azGDORePK8gMaC0QOYAMyEEuzJnyUi"])
+        .stdin(Stdio::null())
+        .output()
+        .expect("Failed to execute binary");
+    assert_eq!(output.status.code(), Some(3));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("use --force"));
+
+    let output = Command::new(get_binary_path())
+        .args(["config", "show", "--config", config_arg])
+        .output()
+        .expect("Failed to execute binary");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(output.status.success());
+    assert!(stdout.contains("user = \"uQiR…\""));
+    assert!(stdout.contains("default_title = \"Backups\""));
+    assert!(!stdout.contains("azGDORePK8gMaC0QOYAMyEEuzJnyUi"));
+
+    let output = Command::new(get_binary_path())
+        .args(["config", "path"])
+        .env_remove("PUSHOVER_CONFIG")
+        .env("XDG_CONFIG_HOME", temp_dir.path())
+        .env("HOME", temp_dir.path().join("home"))
+        .output()
+        .expect("Failed to execute binary");
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines[0], format!("{} (read)", config_arg));
+    assert!(lines[1].ends_with("home/.config/pushover/config.toml (not found)"));
+    assert!(
+        lines.contains(&"/etc/pushover/config.toml (not found)")
+            || lines.contains(&"/etc/pushover/config.toml (read)")
+    );
+}
+
+#[test]
+fn test_config_validate_rejects_placeholders() {
+    let temp_dir = TempDir::new().unwrap();
+    let config_path = create_test_config(&temp_dir);
+    fs::write(
+        &config_path,
+        "[pushover]\nuser = \"__YOUR_USER_KEY__\"\ntoken = \"azGDORePK8gMaC0QOYAMyEEuzJnyUi\"\n",
+    )
+    .unwrap();
+
+    let output = Command::new(get_binary_path())
+        .args([
+            "config",
+            "validate",
+            "--config",
+            config_path.to_str().unwrap(),
+        ])
+        .output()
+        .expect("Failed to execute binary");
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(3));
+    assert!(stderr.contains("user is still the placeholder __YOUR_USER_KEY__"));
+
+    let output = Command::new(get_binary_path())
+        .args(["config", "validate", "--force"])
+        .output()
+        .expect("Failed to execute binary");
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("Invalid option --force for config validate"));
+}
+
+#[test]
+fn test_usage_errors_reported_before_config() {
+    // Argument errors do not depend on finding a config file
+    let output = Command::new(get_binary_path())
+        .args(["--config", "/nonexistent/config.toml", "--bogus"])
+        .output()
+        .expect("Failed to execute binary");
+
+    let stderr = String::from_utf8_lossy(&output.stderr);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stderr.contains("Invalid option --bogus"));
+}
+
 #[test]
 fn test_invalid_option() {
     let temp_dir = TempDir::new().unwrap();
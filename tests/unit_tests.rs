@@ -1,4 +1,7 @@
-use pushover::{parse_url, url_encode, Config, NotificationConfig, PushoverConfig};
+use pushover::{
+    parse_url, url_encode, Config, Message, NotificationConfig, PushoverConfig, MAX_MESSAGE_LEN,
+    MAX_TITLE_LEN, MAX_URL_TITLE_LEN,
+};
 
 #[test]
 fn test_url_encode_basic() {
@@ -128,11 +131,14 @@ fn test_config_structure() {
             user: "test_user_key".to_string(),
             token: "test_app_token".to_string(),
             default_title: Some("Test Server".to_string()),
+            ..Default::default()
         },
         notification: Some(NotificationConfig {
             sound: Some("cosmic".to_string()),
             device: Some("iphone".to_string()),
+            ..Default::default()
         }),
+        ..Default::default()
     };
 
     assert_eq!(config.pushover.user, "test_user_key");
@@ -154,8 +160,10 @@ fn test_config_minimal() {
             user: "user123".to_string(),
             token: "token456".to_string(),
             default_title: None,
+            ..Default::default()
         },
         notification: None,
+        ..Default::default()
     };
 
     assert_eq!(config.pushover.user, "user123");
@@ -176,6 +184,7 @@ fn test_notification_config_partial() {
     let notification = NotificationConfig {
         sound: Some("pushover".to_string()),
         device: None,
+        ..Default::default()
     };
     assert_eq!(notification.sound, Some("pushover".to_string()));
     assert!(notification.device.is_none());
@@ -347,3 +356,43 @@ fn test_url_encode_boundary_conditions() {
     assert!(encoded.contains("+"));
     assert_eq!(encoded.len(), 1000); // 'a' + 999 '+' characters
 }
+
+#[test]
+fn test_message_validate_lengths() {
+    assert!(Message::new("a".repeat(MAX_MESSAGE_LEN)).validate().is_ok());
+    assert!(Message::new("a".repeat(MAX_MESSAGE_LEN + 1))
+        .validate()
+        .is_err());
+
+    let message = Message::new("body").title("t".repeat(MAX_TITLE_LEN + 1));
+    assert!(message.validate().is_err());
+
+    let message = Message::new("body")
+        .url("https://example.com")
+        .url_title("u".repeat(MAX_URL_TITLE_LEN + 1));
+    assert!(message.validate().is_err());
+
+    let message = Message::new("body").device("a_device_name_that_is_far_too_long");
+    assert!(message.validate().is_err());
+}
+
+#[test]
+fn test_message_validate_conflicts() {
+    assert!(Message::new("").validate().is_err());
+    assert!(Message::new("body")
+        .html(true)
+        .monospace(true)
+        .validate()
+        .is_err());
+    assert!(Message::new("body").url_title("Graph").validate().is_err());
+    assert!(Message::new("body").priority(3).validate().is_err());
+    assert!(Message::new("body").ttl(0).validate().is_err());
+
+    let message = Message::new("<b>bold</b>")
+        .html(true)
+        .url("https://example.com")
+        .url_title("Example")
+        .timestamp(1_700_000_000)
+        .ttl(60);
+    assert!(message.validate().is_ok());
+}